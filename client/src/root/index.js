@@ -84,6 +84,7 @@ function saveMoose() {
       image: serialize_painting_to_b64(),
       dimensions: HD.classList.contains('selected') ? 'HD' : 'Default',
       created: (new Date()).toISOString(),
+      parent: sessionStorage.getItem('parent') ?? undefined,
     }),
   });
 }
@@ -165,6 +166,33 @@ function createPaletteBtn(color, sub = false) {
   return b;
 }
 
+function setParent(name) {
+  if (name === null) {
+    sessionStorage.removeItem('parent');
+    NAME_INPUT.placeholder = 'Moose Name';
+  }
+  else {
+    sessionStorage.setItem('parent', name);
+    NAME_INPUT.placeholder = `Remix of ${name}`;
+  }
+}
+
+/** start the painting from an existing moose, which is then credited as the parent when saving. */
+function loadRemix(name) {
+  return fetch(`/moose/${encodeURIComponent(name)}?format=grid`).then(res => {
+    if (!res.ok) throw new Error(`no such moose: ${name}`);
+    return res.json();
+  }).then(moose => {
+    const size = moose.width * moose.height;
+    if (!MOOSE_SIZES.has(size)) throw new Error(`${name} is not a default or HD moose.`);
+    if (size !== MOOSE_SIZE) toggleHD();
+    PAINTER.replacePainting(moose.image);
+    savePaintingChange.call(PAINTER);
+    setParent(name);
+    if (!PAINTER.drawing) PAINTER.draw();
+  });
+}
+
 // exclusively used as the PAINTER#onchange handler.
 function savePaintingChange() {
   // MOOSE_SIZE is just width * height
@@ -193,6 +221,7 @@ function init() {
   NAME_INPUT.addEventListener('change', (e) => {
     sessionStorage.setItem('name', e.target.value);
   });
+  setParent(sessionStorage.getItem('parent'));
 
   const [width, height] = MOOSE_SIZES.get(MOOSE_SIZE);
   const painter = new GridPaint({
@@ -256,6 +285,7 @@ function init() {
 
   CLEAR.addEventListener('click', () => {
     PAINTER.clearWith(DEFAULT_COLOR);
+    setParent(null);
     if (!PAINTER.drawing) PAINTER.draw();
   });
 
@@ -297,6 +327,16 @@ function init() {
   PAINTER.attachHandlers();
   PAINTER.draw();
 
+  // e.g. /?remix=moose
+  const remix = new URLSearchParams(window.location.search).get('remix');
+  if (remix !== null) {
+    // a reload should not throw away changes made since.
+    window.history.replaceState(null, '', window.location.pathname);
+    loadRemix(remix).catch(e => {
+      openModal('Error', e.message);
+    });
+  }
+
   document.addEventListener('keyup', e => {
     if (e.ctrlKey && e.key === 'z') {
       UNDO.click();
@@ -16,6 +16,8 @@
 
 use crate::{
     db::{BulkModeDupe, sqlite3_impl::Sqlite3Error},
+    import::ImportError,
+    model::queries::ArtFormat,
     shared_data::EXAMPLE_CONFIG,
 };
 use bcrypt_pbkdf::bcrypt_pbkdf;
@@ -71,6 +73,10 @@ pub enum ArgsError {
     Usage(String),
     #[error("Cannot get database connection: {0}")]
     DbConn(#[from] Sqlite3Error),
+    #[error("Could not import moose: {0}")]
+    Import(#[from] ImportError),
+    #[error("No such moose to delete: {0}")]
+    NoMoose(String),
 }
 
 #[derive(Deserialize, Clone)]
@@ -129,6 +135,9 @@ pub struct RunConfig {
     cookie_secret: Option<String>,
     pub github_oauth2: Option<GitHubOauth2>,
     pub ratelim: Option<Ratelim>,
+    /// GitHub users allowed to delete any moose.
+    #[serde(default)]
+    pub admins: Vec<String>,
     #[serde(skip)]
     pub cookie_key: Secret,
 }
@@ -219,12 +228,24 @@ struct Comm {
     config: Option<PathBuf>,
     listen: Option<String>,
     dupe: BulkModeDupe,
+    format: Option<ArtFormat>,
+    name: Option<String>,
+    convert_to: ConvertTo,
     subcmd: SubComm,
 }
 pub enum SubComm {
     Run,
     Import(BulkModeDupe, Option<PathBuf>),
-    Convert(Option<(PathBuf, Option<PathBuf>)>),
+    ImportPng(BulkModeDupe, Option<String>, Option<PathBuf>),
+    ImportArt(BulkModeDupe, ArtFormat, String, Option<PathBuf>),
+    Convert(ConvertTo, Option<(PathBuf, Option<PathBuf>)>),
+    Delete(Option<String>),
+}
+
+#[derive(Clone, Copy)]
+pub enum ConvertTo {
+    Moose2,
+    Legacy,
 }
 
 impl Default for Comm {
@@ -233,6 +254,9 @@ impl Default for Comm {
             config: None,
             listen: None,
             dupe: BulkModeDupe::Fail,
+            format: None,
+            name: None,
+            convert_to: ConvertTo::Moose2,
             subcmd: SubComm::Run,
         }
     }
@@ -244,18 +268,27 @@ Options:
     -c | --config=c  Configuration file to read from; default: $CONFIGURATION_DIRECTORY/config.json
                                                               $XDG_CONFIG_HOME/moose2/config.json
     -l | --listen=l  server listen address argument; overrides configuration file.
-    -i | --ignore    for import subcommand: ignore existing duplicate moose (by name).
-    -u | --update    for import subcommand: update existing duplicate moose (by name).
+    -i | --ignore    for import subcommands: ignore existing duplicate moose (by name).
+    -u | --update    for import subcommands: update existing duplicate moose (by name).
+    -f | --format=f  for import subcommand: json (default), irc or ansi text art.
+    -n | --name=n    for import subcommand: name of a text art moose; default: [input] file name.
+         --to-legacy for convert subcommand: convert a moose2 json dump to moose-legacy format instead.
 
 Subcommand:
-    import  [input]      Import moose from [input] json file.
+    import  [input]      Import moose from [input] json file, or a text art file with --format.
+                         Deleted moose are not dumped, so their remixes are imported without a parent.
+    import-png <name> [input]
+                         Import a PNG drawn on a default or HD moose grid as <name>.
     convert [from] [to]  Convert moose json dump to modern moose2 format.
+    delete <name>        Delete a moose, leaving a tombstone so its name stays taken.
 "###;
 
 fn parse_argv() -> Result<Comm, ArgsError> {
     enum F {
         Config,
         Listen,
+        Format,
+        Name,
     }
     let (comm, flag) = std::env::args()
         .skip(1)
@@ -263,7 +296,11 @@ fn parse_argv() -> Result<Comm, ArgsError> {
             if (arg.starts_with("-c")
                 || arg.starts_with("--config")
                 || arg.starts_with("-l")
-                || arg.starts_with("--listen"))
+                || arg.starts_with("--listen")
+                || arg.starts_with("-f")
+                || arg.starts_with("--format")
+                || arg.starts_with("-n")
+                || arg.starts_with("--name"))
                 && let Some((f, v)) = arg.split_once('=')
             {
                 args.push(f.to_owned());
@@ -279,6 +316,15 @@ fn parse_argv() -> Result<Comm, ArgsError> {
                 match flag {
                     F::Config => comm.config = Some(arg.into()),
                     F::Listen => comm.listen = Some(arg.to_owned()),
+                    F::Format => {
+                        comm.format = match arg.as_str() {
+                            "json" => None,
+                            "irc" => Some(ArtFormat::Irc),
+                            "ansi" => Some(ArtFormat::Ansi),
+                            _ => return Err(ArgsError::Usage(format!("Unknown format {arg}."))),
+                        }
+                    }
+                    F::Name => comm.name = Some(arg.to_owned()),
                 }
                 return Ok((comm, None));
             };
@@ -286,8 +332,11 @@ fn parse_argv() -> Result<Comm, ArgsError> {
             match arg.as_str() {
                 "-c" | "--config" => flag_slot = Some(F::Config),
                 "-l" | "--listen" => flag_slot = Some(F::Listen),
+                "-f" | "--format" => flag_slot = Some(F::Format),
+                "-n" | "--name" => flag_slot = Some(F::Name),
                 "-i" | "--ignore" => comm.dupe = BulkModeDupe::Ignore,
                 "-u" | "--update" => comm.dupe = BulkModeDupe::Update,
+                "--to-legacy" => comm.convert_to = ConvertTo::Legacy,
                 "-h" | "--help" => return Err(ArgsError::Usage("".to_owned())),
                 arg if arg.starts_with('-') => {
                     return Err(ArgsError::Usage(format!("Unknown Flag {arg}.")));
@@ -296,7 +345,13 @@ fn parse_argv() -> Result<Comm, ArgsError> {
                     (SubComm::Run, "import") => {
                         comm.subcmd = SubComm::Import(BulkModeDupe::Fail, None)
                     }
-                    (SubComm::Run, "convert") => comm.subcmd = SubComm::Convert(None),
+                    (SubComm::Run, "import-png") => {
+                        comm.subcmd = SubComm::ImportPng(BulkModeDupe::Fail, None, None)
+                    }
+                    (SubComm::Run, "convert") => {
+                        comm.subcmd = SubComm::Convert(ConvertTo::Moose2, None)
+                    }
+                    (SubComm::Run, "delete") => comm.subcmd = SubComm::Delete(None),
                     (SubComm::Run, anything) => {
                         return Err(ArgsError::Usage(format!("Invalid subcommand {anything}.")));
                     }
@@ -306,23 +361,51 @@ fn parse_argv() -> Result<Comm, ArgsError> {
                     (SubComm::Import(_, Some(_)), _) => {
                         return Err(ArgsError::Usage("Too many arguments to import.".to_owned()));
                     }
-                    (SubComm::Convert(None), file) => {
-                        comm.subcmd = SubComm::Convert(Some((file.into(), None)));
+                    (SubComm::ImportPng(d, None, None), name) => {
+                        comm.subcmd = SubComm::ImportPng(d, Some(name.to_owned()), None);
+                    }
+                    (SubComm::ImportPng(d, name @ Some(_), None), file) => {
+                        comm.subcmd = SubComm::ImportPng(d, name, Some(file.into()));
+                    }
+                    (SubComm::ImportPng(_, _, Some(_)), _) => {
+                        return Err(ArgsError::Usage(
+                            "Too many arguments to import-png.".to_owned(),
+                        ));
+                    }
+                    (SubComm::ImportArt(..), _) => {
+                        unreachable!("ImportArt is only created after argv is parsed.")
+                    }
+                    (SubComm::Convert(c, None), file) => {
+                        comm.subcmd = SubComm::Convert(c, Some((file.into(), None)));
                     }
-                    (SubComm::Convert(Some((input, None))), output) => {
-                        comm.subcmd = SubComm::Convert(Some((input, Some(output.into()))));
+                    (SubComm::Convert(c, Some((input, None))), output) => {
+                        comm.subcmd = SubComm::Convert(c, Some((input, Some(output.into()))));
                     }
-                    (SubComm::Convert(Some((_, Some(_)))), _) => {
+                    (SubComm::Convert(_, Some((_, Some(_)))), _) => {
                         return Err(ArgsError::Usage(
                             "Too many files given to convert.".to_owned(),
                         ));
                     }
+                    (SubComm::Delete(None), name) => {
+                        comm.subcmd = SubComm::Delete(Some(name.to_owned()));
+                    }
+                    (SubComm::Delete(Some(_)), _) => {
+                        return Err(ArgsError::Usage("Too many arguments to delete.".to_owned()));
+                    }
                 },
             }
             Ok((comm, flag_slot))
         })?;
     if flag.is_some() {
         Err(ArgsError::Usage("No value given for flag.".to_owned()))
+    } else if let SubComm::ImportPng(_, None, _) = comm.subcmd {
+        Err(ArgsError::Usage(
+            "No moose name given to import-png.".to_owned(),
+        ))
+    } else if let SubComm::Delete(None) = comm.subcmd {
+        Err(ArgsError::Usage(
+            "No moose name given to delete.".to_owned(),
+        ))
     } else {
         Ok(comm)
     }
@@ -331,7 +414,25 @@ fn parse_argv() -> Result<Comm, ArgsError> {
 pub fn parse_args() -> Result<(SubComm, RunConfig), ArgsError> {
     let args = parse_argv()?;
     let sub = match args.subcmd {
-        SubComm::Import(_, input) => SubComm::Import(args.dupe, input),
+        SubComm::Import(_, input) => match args.format {
+            None => SubComm::Import(args.dupe, input),
+            Some(format) => {
+                let name = args
+                    .name
+                    .or_else(|| {
+                        input
+                            .as_ref()
+                            .and_then(|i| i.file_stem())
+                            .map(|stem| stem.to_string_lossy().into_owned())
+                    })
+                    .ok_or_else(|| {
+                        ArgsError::Usage("No moose name given for text art from stdin.".to_owned())
+                    })?;
+                SubComm::ImportArt(args.dupe, format, name, input)
+            }
+        },
+        SubComm::ImportPng(_, name, input) => SubComm::ImportPng(args.dupe, name, input),
+        SubComm::Convert(_, io) => SubComm::Convert(args.convert_to, io),
         sc => sc,
     };
 
@@ -18,8 +18,8 @@ use std::path::PathBuf;
 
 use crate::model::{
     author::AuthenticatedAuthor,
-    moose::Moose,
-    pages::{MooseSearch, MooseSearchPage},
+    moose::{Moose, MooseAncestor, MooseRevision},
+    pages::{MooseSearch, MooseSearchPage, TagCount},
 };
 
 pub mod query;
@@ -57,8 +57,33 @@ pub trait MooseDB<E> {
         page_num: usize,
         author: Option<AuthenticatedAuthor>,
     ) -> Result<MooseSearchPage, E>;
-    async fn insert_moose(&self, moose: Moose) -> Result<(), E>;
-    async fn upvote_moose(&self, author: AuthenticatedAuthor, moose: String) -> Result<(), E>;
+    /// Saves a new moose along with its tags, or neither of them.
+    async fn insert_moose(&self, moose: Moose, tags: Vec<String>) -> Result<(), E>;
+    /// Replaces the image of an existing moose, keeping the old one as a revision;
+    /// returns false if there was no moose to edit.
+    async fn edit_moose(&self, moose: Moose) -> Result<bool, E>;
+    async fn get_revisions(&self, moose: &str) -> Result<Vec<MooseRevision>, E>;
+    /// The moose as it was at the given revision.
+    async fn get_moose_revision(&self, moose: &str, revision: i64) -> Result<Option<Moose>, E>;
+    /// Replaces all the tags of a moose.
+    async fn set_tags(&self, moose: &str, tags: Vec<String>) -> Result<(), E>;
+    async fn get_tags(&self, moose: &str) -> Result<Vec<String>, E>;
+    /// Every tag in use and how many moose have it, most used first.
+    async fn get_tag_counts(&self) -> Result<Vec<TagCount>, E>;
+    async fn get_tag_page(
+        &self,
+        tag: &str,
+        page_num: usize,
+        author: Option<AuthenticatedAuthor>,
+    ) -> Result<MooseSearchPage, E>;
+    /// Names of the live moose that are remixes of this one.
+    async fn get_moose_children(&self, moose: &str) -> Result<Vec<String>, E>;
+    /// The parent of a moose, its parent and so on, closest first.
+    async fn get_moose_ancestry(&self, moose: &str) -> Result<Vec<MooseAncestor>, E>;
+    /// Leaves a tombstone in place of the moose; returns false if there was no moose to delete.
+    async fn delete_moose(&self, moose: &str) -> Result<bool, E>;
+    /// Returns false if there was no moose to upvote.
+    async fn upvote_moose(&self, author: AuthenticatedAuthor, moose: String) -> Result<bool, E>;
     async fn unvote_moose(&self, author: AuthenticatedAuthor, moose: String) -> Result<(), E>;
     async fn dump_moose(&self, path: PathBuf) -> Result<(), E>;
     async fn bulk_import(
@@ -66,6 +91,7 @@ pub trait MooseDB<E> {
         moose_in: Option<PathBuf>,
         dup_behavior: BulkModeDupe,
     ) -> Result<(), E>;
+    async fn bulk_insert(&self, moose_in: Vec<Moose>, dup_behavior: BulkModeDupe) -> Result<(), E>;
     async fn get_cache_key(&self) -> Result<String, E>;
     async fn check_pool(&self) -> Result<(), E>;
 }
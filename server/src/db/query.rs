@@ -22,6 +22,9 @@ PRAGMA busy_timeout = 5000;
 PRAGMA cache_size = 512;
 PRAGMA temp_store = MEMORY;
 
+-- Everything here is IF NOT EXISTS, so a trigger that already exists keeps the body it was created with.
+-- New side effects on a table get a trigger of their own instead of being added to an existing one.
+
 CREATE TABLE IF NOT EXISTS Moose
   ( name       TEXT    PRIMARY KEY
   -- used for keyset offsetting.
@@ -32,20 +35,52 @@ CREATE TABLE IF NOT EXISTS Moose
   , author     TEXT    DEFAULT NULL
   -- it's either this or N*M joining on Vote table.
   , upvotes    INTEGER DEFAULT 0
+  -- added after the fact, see MOOSE_COLUMN_MIGRATIONS.
+  , description TEXT    DEFAULT NULL
+  -- the moose this one is a remix of. App deletes leave a tombstone, so the link stays.
+  , parent      TEXT    DEFAULT NULL REFERENCES Moose (name) ON DELETE SET NULL
   ) WITHOUT ROWID;
 CREATE UNIQUE INDEX IF NOT EXISTS Moose_PosIdx    ON Moose(pos);
 CREATE        INDEX IF NOT EXISTS Moose_AuthorIdx ON Moose(author);
+CREATE        INDEX IF NOT EXISTS Moose_ParentIdx ON Moose(parent);
 
 CREATE VIRTUAL TABLE IF NOT EXISTS MooseSearch USING fts5
   ( moose_name, tokenize = 'porter unicode61' );
 
+-- Most moose have no description, so they get their own index instead of an empty column in MooseSearch.
+CREATE VIRTUAL TABLE IF NOT EXISTS MooseDescriptionSearch USING fts5
+  ( moose_name UNINDEXED, description, tokenize = 'porter unicode61' );
+
 CREATE TRIGGER IF NOT EXISTS Moose_InsertTrigger
 AFTER INSERT ON Moose
 BEGIN
   INSERT INTO MooseSearch(moose_name) VALUES (NEW.name);
 END;
 
--- Deletes happen through sqlite3 shell, not the app.
+CREATE TRIGGER IF NOT EXISTS Moose_InsertDescriptionTrigger
+AFTER INSERT ON Moose
+WHEN NEW.description IS NOT NULL
+BEGIN
+  INSERT INTO MooseDescriptionSearch(moose_name, description) VALUES (NEW.name, NEW.description);
+END;
+
+CREATE TRIGGER IF NOT EXISTS Moose_UpdateDescriptionTrigger
+AFTER UPDATE OF description ON Moose
+BEGIN
+  DELETE FROM MooseDescriptionSearch WHERE moose_name = OLD.name;
+  INSERT INTO MooseDescriptionSearch(moose_name, description)
+  SELECT NEW.name, NEW.description
+   WHERE NEW.description IS NOT NULL;
+END;
+
+CREATE TRIGGER IF NOT EXISTS Moose_DeleteDescriptionTrigger
+AFTER DELETE ON Moose
+BEGIN
+  DELETE FROM MooseDescriptionSearch WHERE moose_name = OLD.name;
+END;
+
+-- Deletes from the app leave a tombstone instead, see MooseTombstone.
+-- Real deletes happen through sqlite3 shell and renumber every moose after it.
 CREATE TRIGGER IF NOT EXISTS Moose_DeleteTrigger
 AFTER DELETE ON Moose
 BEGIN
@@ -58,8 +93,38 @@ BEGIN
   UPDATE Moose SET pos = -(pos + 1) WHERE pos < 0;
 END;
 
+-- Every image a moose had before its author edited it.
+-- Revisions count up from 0, the image the moose was created with.
+CREATE TABLE IF NOT EXISTS MooseRevision
+  ( moose_name TEXT    NOT NULL
+  , revision   INTEGER NOT NULL
+  , image      BLOB    NOT NULL
+  , dimensions TEXT    NOT NULL
+  -- when this image was replaced by a newer one.
+  , replaced   TEXT    NOT NULL
+  , FOREIGN KEY (moose_name) REFERENCES Moose (name) ON DELETE CASCADE
+  , PRIMARY KEY (moose_name, revision)
+  ) WITHOUT ROWID;
+
+-- Themes like holidays, memes or team mascots; see validate_tag for what a tag looks like.
+CREATE TABLE IF NOT EXISTS MooseTag
+  ( moose_name TEXT NOT NULL
+  , tag        TEXT NOT NULL
+  , FOREIGN KEY (moose_name) REFERENCES Moose (name) ON DELETE CASCADE
+  , PRIMARY KEY (moose_name, tag)
+  ) WITHOUT ROWID;
+CREATE        INDEX IF NOT EXISTS MooseTag_TagIdx ON MooseTag(tag);
+
+-- Deleted moose keep their row, so their name stays taken and every later moose keeps its pos,
+-- but they are hidden from everything else: pages, search, the dump, random, latest...
+CREATE TABLE IF NOT EXISTS MooseTombstone
+  ( moose_name TEXT PRIMARY KEY
+  , deleted    TEXT NOT NULL
+  , FOREIGN KEY (moose_name) REFERENCES Moose (name) ON DELETE CASCADE
+  ) WITHOUT ROWID;
+
 -- This key is intended for invalidating moose page views
--- currently only happens when votes occur.
+-- currently happens when votes occur or a moose is edited.
 CREATE TABLE IF NOT EXISTS CacheKey
   ( id    INTEGER PRIMARY KEY CHECK ( id = 0 )
   , ckey  TEXT NOT NULL
@@ -77,6 +142,37 @@ CREATE TABLE IF NOT EXISTS Vote
   ) WITHOUT ROWID;
 CREATE        INDEX IF NOT EXISTS Vote_ByMNameIdx on Vote(moose_name);
 
+CREATE TRIGGER IF NOT EXISTS Moose_UpdateImageTrigger
+AFTER UPDATE OF image ON Moose
+BEGIN
+  UPDATE CacheKey
+     SET ckey = hex(randomblob(16))
+   WHERE id = 0;
+END;
+
+CREATE TRIGGER IF NOT EXISTS MooseTombstone_InsertTrigger
+AFTER INSERT ON MooseTombstone
+BEGIN
+  DELETE FROM MooseSearch   WHERE moose_name = NEW.moose_name;
+  DELETE FROM MooseRevision WHERE moose_name = NEW.moose_name;
+  DELETE FROM Vote          WHERE moose_name = NEW.moose_name;
+  -- the image is gone for good; this also invalidates the page cache key.
+  UPDATE Moose SET image = X'' WHERE name = NEW.moose_name;
+END;
+
+-- Clearing the description drops it from MooseDescriptionSearch too.
+CREATE TRIGGER IF NOT EXISTS MooseTombstone_DescriptionTrigger
+AFTER INSERT ON MooseTombstone
+BEGIN
+  UPDATE Moose SET description = NULL WHERE name = NEW.moose_name;
+END;
+
+CREATE TRIGGER IF NOT EXISTS MooseTombstone_TagTrigger
+AFTER INSERT ON MooseTombstone
+BEGIN
+  DELETE FROM MooseTag WHERE moose_name = NEW.moose_name;
+END;
+
 CREATE TRIGGER IF NOT EXISTS Vote_InsertTrigger
 AFTER INSERT ON Vote
 BEGIN
@@ -111,25 +207,65 @@ BEGIN
 END;
 "###;
 
-pub const INSERT_VOTE: &str =
-    "INSERT INTO Vote(author_name, moose_name, vote_type) VALUES (?, ?, ?)";
+/// Databases made before a Moose column existed need it before CREATE_TABLE sets up anything using it.
+pub const NEEDS_MOOSE_COLUMN: &str = r###"
+    SELECT EXISTS ( SELECT 1 FROM pragma_table_info('Moose') )
+       AND NOT EXISTS ( SELECT 1 FROM pragma_table_info('Moose') WHERE name = ?1 )
+"###;
+
+/// Columns added to Moose after the fact, in order, and how to add them.
+pub const MOOSE_COLUMN_MIGRATIONS: &[(&str, &str)] = &[
+    // There are no descriptions to index yet, so MooseDescriptionSearch can start out empty.
+    (
+        "description",
+        "ALTER TABLE Moose ADD COLUMN description TEXT DEFAULT NULL",
+    ),
+    (
+        "parent",
+        "ALTER TABLE Moose ADD COLUMN parent TEXT DEFAULT NULL REFERENCES Moose (name) ON DELETE SET NULL",
+    ),
+];
+
+/// Does nothing if the moose does not exist or is deleted.
+pub const INSERT_VOTE: &str = r###"
+    INSERT INTO Vote(author_name, moose_name, vote_type)
+    SELECT ?1, name, ?3
+      FROM Moose
+     WHERE name = ?2 AND name NOT IN ( SELECT moose_name FROM MooseTombstone )
+"###;
 
 pub const DELETE_VOTE: &str = "DELETE FROM Vote WHERE author_name = ? AND moose_name = ?";
 
 pub const LAST_MOOSE: &str = r###"
-    SELECT name, image, dimensions, created, author, upvotes
+    SELECT name, image, dimensions, created, author, upvotes, description, parent
       FROM Moose
-     WHERE pos = ( SELECT MAX(pos) FROM Moose )
+     WHERE pos = ( SELECT MAX(pos) FROM Moose WHERE name NOT IN ( SELECT moose_name FROM MooseTombstone ) )
 "###;
 
 // COUNT is always a table scan?
 pub const LEN_MOOSE: &str = "SELECT COALESCE(MAX(pos) + 1, 0) FROM Moose";
 
-pub const GET_MOOSE: &str =
-    "SELECT name, image, dimensions, created, author, upvotes FROM Moose WHERE name = ?";
+pub const GET_MOOSE: &str = r###"
+    SELECT name, image, dimensions, created, author, upvotes, description, parent
+      FROM Moose
+     WHERE name = ? AND name NOT IN ( SELECT moose_name FROM MooseTombstone )
+"###;
 
-pub const GET_MOOSE_IDX: &str =
-    "SELECT name, image, dimensions, created, author, upvotes FROM Moose WHERE pos = ?";
+/// The moose at the given pos, unless it was deleted.
+pub const GET_MOOSE_AT_POS: &str = r###"
+    SELECT name, image, dimensions, created, author, upvotes, description, parent
+      FROM Moose
+     WHERE pos = ? AND name NOT IN ( SELECT moose_name FROM MooseTombstone )
+"###;
+
+/// The first moose at or after the given pos, skipping deleted moose.
+pub const GET_MOOSE_IDX: &str = r###"
+    SELECT name, image, dimensions, created, author, upvotes, description, parent
+      FROM Moose
+     WHERE pos >= ? AND name NOT IN ( SELECT moose_name FROM MooseTombstone )
+  ORDER BY pos
+     LIMIT 1
+"###;
 
 pub const GET_MOOSE_PAGE: &str = r###"
     SELECT m.name
@@ -138,9 +274,12 @@ pub const GET_MOOSE_PAGE: &str = r###"
          , m.created
          , m.author
          , m.upvotes
+         , m.description
+         , m.parent
          , ?3
       FROM Moose m
      WHERE m.pos >= ?1 AND m.pos < ?2
+       AND m.name NOT IN ( SELECT moose_name FROM MooseTombstone )
      ORDER BY pos
 "###;
 
@@ -151,24 +290,64 @@ pub const GET_MOOSE_PAGE_AND_USER_VOTE: &str = r###"
          , m.created
          , m.author
          , m.upvotes
+         , m.description
+         , m.parent
          , v.vote_type
       FROM Moose m
  LEFT JOIN Vote v
         ON v.author_name = ?3 AND v.moose_name = m.name
      WHERE m.pos >= ?1 AND m.pos < ?2
+       AND m.name NOT IN ( SELECT moose_name FROM MooseTombstone )
      ORDER BY pos
 "###;
 
 pub const GET_CACHE_KEY: &str = "SELECT ckey FROM CacheKey WHERE id = 0";
 
-pub const SEARCH_MOOSE_PAGE: &str = const_format::formatcp!(
+/// Moose with every tag in the JSON array ?3.
+const TAGGED_MOOSE: &str = r###"
+        SELECT moose_name
+          FROM MooseTag
+         WHERE tag IN ( SELECT value FROM json_each(?3) )
+           AND moose_name NOT IN ( SELECT moose_name FROM MooseTombstone )
+      GROUP BY moose_name
+        HAVING COUNT(*) = json_array_length(?3)
+"###;
+
+/// Moose whose name or description matches ?1, only those with the tags in ?3 if there are any.
+/// Name matches come first, a description is only a tie breaker.
+const NAME_SEARCH: &str = const_format::formatcp!(
     r###"
+        SELECT moose_name
+          FROM ( SELECT moose_name, 0 AS by_desc, rank
+                   FROM MooseSearch
+                  WHERE moose_name MATCH ?1
+              UNION ALL
+                 SELECT moose_name, 1 AS by_desc, rank
+                   FROM MooseDescriptionSearch
+                  WHERE description MATCH ?1
+               )
+         WHERE json_array_length(?3) = 0 OR moose_name IN ( {0} )
+      GROUP BY moose_name
+      ORDER BY MIN(by_desc), MIN(rank)
+         LIMIT {1}
+"###,
+    TAGGED_MOOSE,
+    crate::model::PAGE_SIZE * crate::model::PAGE_SEARCH_LIM
+);
+
+/// Searches with only tag: terms have no name to MATCH, so ?1 goes unused.
+const TAG_SEARCH: &str = const_format::formatcp!(
+    "{0} LIMIT {1}",
+    TAGGED_MOOSE,
+    crate::model::PAGE_SIZE * crate::model::PAGE_SEARCH_LIM
+);
+
+macro_rules! search_moose_page {
+    ($search:expr) => {
+        const_format::formatcp!(
+            r###"
     WITH search_res(moose_name) AS
-      ( SELECT moose_name
-          FROM MooseSearch
-         WHERE moose_name MATCH ?1
-         ORDER BY RANK
-         LIMIT {0}
+      ( {0}
       )
     SELECT m.name
          , m.image
@@ -176,23 +355,25 @@ pub const SEARCH_MOOSE_PAGE: &str = const_format::formatcp!(
          , m.created
          , m.author
          , m.upvotes
+         , m.description
+         , m.parent
          , m.pos
          , ?2
       FROM Moose m
 INNER JOIN search_res
         ON m.name == moose_name
 "###,
-    crate::model::PAGE_SIZE * crate::model::PAGE_SEARCH_LIM
-);
+            $search
+        )
+    };
+}
 
-pub const SEARCH_MOOSE_PAGE_AND_USER_VOTE: &str = const_format::formatcp!(
-    r###"
+macro_rules! search_moose_page_and_user_vote {
+    ($search:expr) => {
+        const_format::formatcp!(
+            r###"
     WITH search_res(moose_name) AS
-      ( SELECT moose_name
-          FROM MooseSearch
-         WHERE moose_name MATCH ?1
-         ORDER BY RANK
-         LIMIT {0}
+      ( {0}
       )
     , search_w_vote(moose_name, vote_type) AS
       ( SELECT sr.moose_name, v.vote_type
@@ -206,20 +387,148 @@ pub const SEARCH_MOOSE_PAGE_AND_USER_VOTE: &str = const_format::formatcp!(
          , m.created
          , m.author
          , m.upvotes
+         , m.description
+         , m.parent
          , m.pos
          , swv.vote_type
       FROM Moose m
 INNER JOIN search_w_vote swv
         ON m.name == swv.moose_name
 "###,
-    crate::model::PAGE_SIZE * crate::model::PAGE_SEARCH_LIM
+            $search
+        )
+    };
+}
+
+pub const SEARCH_MOOSE_PAGE: &str = search_moose_page!(NAME_SEARCH);
+pub const SEARCH_MOOSE_PAGE_AND_USER_VOTE: &str = search_moose_page_and_user_vote!(NAME_SEARCH);
+pub const SEARCH_TAG_PAGE: &str = search_moose_page!(TAG_SEARCH);
+pub const SEARCH_TAG_PAGE_AND_USER_VOTE: &str = search_moose_page_and_user_vote!(TAG_SEARCH);
+
+pub const GET_TAG_PAGE: &str = const_format::formatcp!(
+    r###"
+    SELECT m.name
+         , m.image
+         , m.dimensions
+         , m.created
+         , m.author
+         , m.upvotes
+         , m.description
+         , m.parent
+         , m.pos
+         , v.vote_type
+      FROM MooseTag t
+INNER JOIN Moose m
+        ON m.name == t.moose_name
+ LEFT JOIN Vote v
+        ON v.author_name = ?2 AND v.moose_name = m.name
+     WHERE t.tag = ?1
+       AND m.name NOT IN ( SELECT moose_name FROM MooseTombstone )
+  ORDER BY m.pos
+     LIMIT {0} OFFSET ?3
+"###,
+    crate::model::PAGE_SIZE
 );
 
-pub const UPDATE_MOOSE: &str = "UPDATE Moose SET image = ?2, dimensions = ?3, created = ?4, author = ?5, upvotes = ?6 WHERE name = ?1";
+pub const COUNT_TAG: &str = r###"
+    SELECT COUNT(*)
+      FROM MooseTag
+     WHERE tag = ? AND moose_name NOT IN ( SELECT moose_name FROM MooseTombstone )
+"###;
+
+pub const GET_TAG_COUNTS: &str = r###"
+    SELECT tag, COUNT(*) AS moose_count
+      FROM MooseTag
+     WHERE moose_name NOT IN ( SELECT moose_name FROM MooseTombstone )
+  GROUP BY tag
+  ORDER BY moose_count DESC, tag ASC
+"###;
+
+pub const GET_MOOSE_TAGS: &str = "SELECT tag FROM MooseTag WHERE moose_name = ? ORDER BY tag";
 
+pub const DELETE_MOOSE_TAGS: &str = "DELETE FROM MooseTag WHERE moose_name = ?";
+
+pub const INSERT_MOOSE_TAG: &str = "INSERT INTO MooseTag(moose_name, tag) VALUES (?, ?)";
+
+pub const UPDATE_MOOSE: &str = "UPDATE Moose SET image = ?2, dimensions = ?3, created = ?4, author = ?5, upvotes = ?6, description = ?7, parent = ( SELECT name FROM Moose WHERE name = ?8 AND name <> ?1 ) WHERE name = ?1 AND name NOT IN ( SELECT moose_name FROM MooseTombstone )";
+
+/// Only the image changes, so the moose keeps its pos, author and votes; deleted moose are left alone.
+pub const UPDATE_MOOSE_IMAGE: &str = "UPDATE Moose SET image = ?2, dimensions = ?3 WHERE name = ?1 AND name NOT IN ( SELECT moose_name FROM MooseTombstone )";
+
+pub const INSERT_REVISION: &str = r###"
+    INSERT INTO MooseRevision(moose_name, revision, image, dimensions, replaced)
+    SELECT name
+         , ( SELECT COALESCE(MAX(revision) + 1, 0) FROM MooseRevision WHERE moose_name = ?1 )
+         , image
+         , dimensions
+         , ?2
+      FROM Moose
+     WHERE name = ?1
+"###;
+
+pub const GET_REVISIONS: &str = r###"
+    SELECT revision, dimensions, replaced
+      FROM MooseRevision
+     WHERE moose_name = ?
+  ORDER BY revision ASC
+"###;
+
+pub const GET_MOOSE_REVISION: &str = r###"
+    SELECT m.name, r.image, r.dimensions, m.created, m.author, m.upvotes, m.description, m.parent
+      FROM MooseRevision r
+INNER JOIN Moose m
+        ON m.name == r.moose_name
+     WHERE r.moose_name = ? AND r.revision = ?
+"###;
+
+/// Does nothing if the moose does not exist or is already deleted.
+pub const INSERT_TOMBSTONE: &str = r###"
+    INSERT OR IGNORE INTO MooseTombstone(moose_name, deleted)
+    SELECT name, ?2
+      FROM Moose
+     WHERE name = ?1
+"###;
+
+pub const HAS_MOOSE: &str = "SELECT EXISTS ( SELECT 1 FROM Moose WHERE name = ? )";
+
+/// A parent that does not exist, e.g. one left out of a dump because it was deleted, is dropped.
 pub const INSERT_MOOSE_WITH_COMPUTED_POS: &str = r###"
-    INSERT INTO Moose(name,                                            pos, image, dimensions, created, author, upvotes)
-    VALUES           (   ?,  (SELECT COALESCE(MAX(pos) + 1, 0) FROM Moose),     ?,          ?,       ?,      ?,       ?);
+    INSERT INTO Moose(name,                                            pos, image, dimensions, created, author, upvotes, description,                                     parent)
+    VALUES           (   ?,  (SELECT COALESCE(MAX(pos) + 1, 0) FROM Moose),     ?,          ?,       ?,      ?,       ?,           ?, (SELECT name FROM Moose WHERE name = ?));
 "###;
 
-pub const DUMP_MOOSE: &str = "SELECT name, image, dimensions, created, author, upvotes FROM Moose";
+/// Live remixes of a moose, oldest first.
+pub const GET_MOOSE_CHILDREN: &str = r###"
+    SELECT name
+      FROM Moose
+     WHERE parent = ? AND name NOT IN ( SELECT moose_name FROM MooseTombstone )
+  ORDER BY pos
+"###;
+
+/// The parent of a moose, its parent, and so on; deleted moose included so the chain is not broken.
+/// Bulk updates can make cycles, so the walk stops after MOOSE_MAX_ANCESTRY moose.
+pub const GET_MOOSE_ANCESTRY: &str = const_format::formatcp!(
+    r###"
+    WITH RECURSIVE ancestry(name, depth) AS
+      ( SELECT parent, 1 FROM Moose WHERE name = ?1 AND parent IS NOT NULL
+     UNION ALL
+        SELECT m.parent, a.depth + 1
+          FROM Moose m
+    INNER JOIN ancestry a
+            ON m.name == a.name
+         WHERE m.parent IS NOT NULL AND a.depth < {0}
+      )
+    SELECT a.name, t.moose_name IS NOT NULL
+      FROM ancestry a
+ LEFT JOIN MooseTombstone t
+        ON t.moose_name == a.name
+  ORDER BY a.depth
+"###,
+    crate::model::moose::MOOSE_MAX_ANCESTRY
+);
+
+pub const DUMP_MOOSE: &str = r###"
+    SELECT name, image, dimensions, created, author, upvotes, description, parent
+      FROM Moose
+     WHERE name NOT IN ( SELECT moose_name FROM MooseTombstone )
+"###;
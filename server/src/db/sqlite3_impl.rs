@@ -12,8 +12,8 @@ use crate::{
     model::{
         PAGE_SEARCH_LIM, PAGE_SIZE,
         author::{AuthenticatedAuthor, Author},
-        moose::{Moose, MooseAny, MooseToSqlParams},
-        pages::{MooseSearch, MooseSearchPage},
+        moose::{Moose, MooseAncestor, MooseAny, MooseRevision, MooseToSqlParams},
+        pages::{MooseSearch, MooseSearchPage, TagCount},
         votes::VoteFlag,
     },
 };
@@ -21,18 +21,26 @@ use crate::{
 use super::{
     BulkModeDupe, MooseDB,
     query::{
-        GET_MOOSE, GET_MOOSE_IDX, GET_MOOSE_PAGE, INSERT_MOOSE_WITH_COMPUTED_POS, LAST_MOOSE,
-        LEN_MOOSE, SEARCH_MOOSE_PAGE, UPDATE_MOOSE,
+        COUNT_TAG, DELETE_MOOSE_TAGS, GET_MOOSE, GET_MOOSE_ANCESTRY, GET_MOOSE_AT_POS,
+        GET_MOOSE_CHILDREN, GET_MOOSE_IDX, GET_MOOSE_PAGE, GET_MOOSE_REVISION, GET_MOOSE_TAGS,
+        GET_REVISIONS, GET_TAG_COUNTS, GET_TAG_PAGE, HAS_MOOSE, INSERT_MOOSE_TAG,
+        INSERT_MOOSE_WITH_COMPUTED_POS, INSERT_REVISION, INSERT_TOMBSTONE, LAST_MOOSE, LEN_MOOSE,
+        SEARCH_MOOSE_PAGE, SEARCH_TAG_PAGE, SEARCH_TAG_PAGE_AND_USER_VOTE, UPDATE_MOOSE,
+        UPDATE_MOOSE_IMAGE,
     },
     utils::escape_query,
 };
 
 use rand::Rng;
 use rusqlite::{Connection, OptionalExtension, Params, params};
+use time::OffsetDateTime;
 
 pub type Pool = deadpool_sqlite::Pool;
 pub type PoolConnection = deadpool_sqlite::Object;
 
+/// how many random pos are tried before random() falls back to the next live moose.
+const RANDOM_TRIES: usize = 8;
+
 #[derive(thiserror::Error, Debug)]
 pub enum Sqlite3Error {
     #[error("Pool Connection Error: {0}")]
@@ -59,6 +67,14 @@ fn already_exists(e: &rusqlite::Error) -> bool {
     }
 }
 
+fn insert_tags(conn: &Connection, moose: &str, tags: &[String]) -> Result<(), rusqlite::Error> {
+    tags.iter().try_for_each(|tag| {
+        conn.prepare_cached(INSERT_MOOSE_TAG)?
+            .execute([moose, tag])
+            .map(|_| ())
+    })
+}
+
 fn query_moose<P: Params>(
     conn: &Connection,
     sql: &'static str,
@@ -107,8 +123,23 @@ impl MooseDB<Sqlite3Error> for Pool {
             if len == 0 {
                 return Ok(None);
             }
-            let rand_idx = rand::thread_rng().r#gen_range(0..len);
-            let res = query_moose(&tx, GET_MOOSE_IDX, [rand_idx])?;
+            let mut rng = rand::thread_rng();
+            // draw again when landing on a deleted moose, taking the next live one would favour
+            // moose right after a run of deleted ones.
+            for _ in 0..RANDOM_TRIES {
+                if let Some(moose) = query_moose(&tx, GET_MOOSE_AT_POS, [rng.r#gen_range(0..len)])?
+                {
+                    tx.commit()?;
+                    return Ok(Some(moose));
+                }
+            }
+            // mostly deleted moose, settle for the biased pick; wrap around if there are none
+            // left after rand_idx.
+            let rand_idx = rng.r#gen_range(0..len);
+            let res = match query_moose(&tx, GET_MOOSE_IDX, [rand_idx])? {
+                Some(moose) => Some(moose),
+                None => query_moose(&tx, GET_MOOSE_IDX, [0])?,
+            };
             tx.commit()?;
             Ok(res)
         })
@@ -154,7 +185,7 @@ impl MooseDB<Sqlite3Error> for Pool {
                     .query_map(params![start, end, author], |row| {
                         Ok(MooseSearch {
                             page: page_num,
-                            voted: row.get(6)?,
+                            voted: row.get(8)?,
                             moose: row.try_into()?,
                         })
                     })?
@@ -183,20 +214,23 @@ impl MooseDB<Sqlite3Error> for Pool {
         author: Option<AuthenticatedAuthor>,
     ) -> Result<MooseSearchPage, Sqlite3Error> {
         let conn = self.get().await?;
-        let query = escape_query(query);
+        let (query, tags) = escape_query(query);
+        let tag_only = query.is_empty() && !tags.is_empty();
+        let tags = serde_json::to_string(&tags)?;
         let q = conn
             .interact(move |conn| -> Result<MooseSearchPage, rusqlite::Error> {
-                let (sql_query, author) = if let Some(author) = author {
-                    (SEARCH_MOOSE_PAGE_AND_USER_VOTE, author.into())
-                } else {
-                    (SEARCH_MOOSE_PAGE, Author::Anonymous)
+                let (sql_query, author) = match (author, tag_only) {
+                    (Some(author), false) => (SEARCH_MOOSE_PAGE_AND_USER_VOTE, author.into()),
+                    (Some(author), true) => (SEARCH_TAG_PAGE_AND_USER_VOTE, author.into()),
+                    (None, false) => (SEARCH_MOOSE_PAGE, Author::Anonymous),
+                    (None, true) => (SEARCH_TAG_PAGE, Author::Anonymous),
                 };
                 let result = conn
                     .prepare_cached(sql_query)?
-                    .query_map(params![query, author], |row| {
+                    .query_map(params![query, author, tags], |row| {
                         Ok(MooseSearch {
-                            page: row.get::<_, usize>(6)? / PAGE_SIZE,
-                            voted: row.get(7)?,
+                            page: row.get::<_, usize>(8)? / PAGE_SIZE,
+                            voted: row.get(9)?,
                             moose: row.try_into()?,
                         })
                     })?
@@ -236,36 +270,205 @@ impl MooseDB<Sqlite3Error> for Pool {
         }
     }
 
-    async fn insert_moose(&self, moose: Moose) -> Result<(), Sqlite3Error> {
+    async fn insert_moose(&self, moose: Moose, tags: Vec<String>) -> Result<(), Sqlite3Error> {
         let conn = self.get().await?;
         conn.interact(move |conn| {
-            conn.prepare_cached(INSERT_MOOSE_WITH_COMPUTED_POS)
-                .unwrap()
-                .execute(MooseToSqlParams::from(&moose))
+            let tx = conn.transaction_with_behavior(rusqlite::TransactionBehavior::Immediate)?;
+            tx.prepare_cached(INSERT_MOOSE_WITH_COMPUTED_POS)?
+                .execute(MooseToSqlParams::from(&moose))?;
+            insert_tags(&tx, &moose.name, &tags)?;
+            tx.commit()
         })
         .await
         .unwrap()?;
         Ok(())
     }
 
-    // only upvotes or no vote for now...
-    async fn upvote_moose(
+    async fn edit_moose(&self, moose: Moose) -> Result<bool, Sqlite3Error> {
+        let conn = self.get().await?;
+        let edited = conn
+            .interact(move |conn| {
+                let tx =
+                    conn.transaction_with_behavior(rusqlite::TransactionBehavior::Immediate)?;
+                tx.prepare_cached(INSERT_REVISION)?
+                    .execute(params![moose.name, OffsetDateTime::now_utc()])?;
+                let edited = tx.prepare_cached(UPDATE_MOOSE_IMAGE)?.execute(params![
+                    moose.name,
+                    moose.image,
+                    moose.dimensions
+                ])?;
+                // the moose was deleted since it was looked up; dropping tx rolls back the revision.
+                if edited == 0 {
+                    return Ok(false);
+                }
+                tx.commit().map(|_| true)
+            })
+            .await
+            .unwrap()?;
+        Ok(edited)
+    }
+
+    async fn get_revisions(&self, moose: &str) -> Result<Vec<MooseRevision>, Sqlite3Error> {
+        let conn = self.get().await?;
+        let moose = moose.to_owned();
+        conn.interact(move |conn| {
+            conn.prepare_cached(GET_REVISIONS)?
+                .query_map([moose], |row| row.try_into())?
+                .collect::<Result<Vec<MooseRevision>, rusqlite::Error>>()
+        })
+        .await
+        .unwrap()
+        .map_err(|e| e.into())
+    }
+
+    async fn get_moose_revision(
         &self,
-        author: AuthenticatedAuthor,
-        moose: String,
-    ) -> Result<(), Sqlite3Error> {
+        moose: &str,
+        revision: i64,
+    ) -> Result<Option<Moose>, Sqlite3Error> {
         let conn = self.get().await?;
-        let author = Author::from(author);
+        let moose = moose.to_owned();
+        conn.interact(move |conn| query_moose(conn, GET_MOOSE_REVISION, params![moose, revision]))
+            .await
+            .unwrap()
+    }
+
+    async fn delete_moose(&self, moose: &str) -> Result<bool, Sqlite3Error> {
+        let conn = self.get().await?;
+        let moose = moose.to_owned();
+        let deleted = conn
+            .interact(move |conn| {
+                conn.prepare_cached(INSERT_TOMBSTONE)?
+                    .execute(params![moose, OffsetDateTime::now_utc()])
+            })
+            .await
+            .unwrap()?;
+        Ok(deleted == 1)
+    }
+
+    async fn get_moose_children(&self, moose: &str) -> Result<Vec<String>, Sqlite3Error> {
+        let conn = self.get().await?;
+        let moose = moose.to_owned();
         conn.interact(move |conn| {
-            conn.prepare_cached(INSERT_VOTE)
-                .unwrap()
-                .execute(params![author, moose, VoteFlag::Up])
+            conn.prepare_cached(GET_MOOSE_CHILDREN)?
+                .query_map([moose], |row| row.get(0))?
+                .collect::<Result<Vec<String>, rusqlite::Error>>()
+        })
+        .await
+        .unwrap()
+        .map_err(|e| e.into())
+    }
+
+    async fn get_moose_ancestry(&self, moose: &str) -> Result<Vec<MooseAncestor>, Sqlite3Error> {
+        let conn = self.get().await?;
+        let moose = moose.to_owned();
+        conn.interact(move |conn| {
+            conn.prepare_cached(GET_MOOSE_ANCESTRY)?
+                .query_map([moose], |row| row.try_into())?
+                .collect::<Result<Vec<MooseAncestor>, rusqlite::Error>>()
+        })
+        .await
+        .unwrap()
+        .map_err(|e| e.into())
+    }
+
+    async fn set_tags(&self, moose: &str, tags: Vec<String>) -> Result<(), Sqlite3Error> {
+        let conn = self.get().await?;
+        let moose = moose.to_owned();
+        conn.interact(move |conn| {
+            let tx = conn.transaction_with_behavior(rusqlite::TransactionBehavior::Immediate)?;
+            tx.prepare_cached(DELETE_MOOSE_TAGS)?.execute([&moose])?;
+            insert_tags(&tx, &moose, &tags)?;
+            tx.commit()
         })
         .await
         .unwrap()?;
         Ok(())
     }
 
+    async fn get_tags(&self, moose: &str) -> Result<Vec<String>, Sqlite3Error> {
+        let conn = self.get().await?;
+        let moose = moose.to_owned();
+        conn.interact(move |conn| {
+            conn.prepare_cached(GET_MOOSE_TAGS)?
+                .query_map([moose], |row| row.get(0))?
+                .collect::<Result<Vec<String>, rusqlite::Error>>()
+        })
+        .await
+        .unwrap()
+        .map_err(|e| e.into())
+    }
+
+    async fn get_tag_counts(&self) -> Result<Vec<TagCount>, Sqlite3Error> {
+        let conn = self.get().await?;
+        conn.interact(|conn| {
+            conn.prepare_cached(GET_TAG_COUNTS)?
+                .query_map([], |row| {
+                    Ok(TagCount {
+                        tag: row.get(0)?,
+                        count: row.get(1)?,
+                    })
+                })?
+                .collect::<Result<Vec<TagCount>, rusqlite::Error>>()
+        })
+        .await
+        .unwrap()
+        .map_err(|e| e.into())
+    }
+
+    async fn get_tag_page(
+        &self,
+        tag: &str,
+        page_num: usize,
+        author: Option<AuthenticatedAuthor>,
+    ) -> Result<MooseSearchPage, Sqlite3Error> {
+        let conn = self.get().await?;
+        let tag = tag.to_owned();
+        let author = author.map(Author::from).unwrap_or_default();
+        conn.interact(move |conn| {
+            let count: usize = conn
+                .prepare_cached(COUNT_TAG)?
+                .query_row([&tag], |row| row.get(0))?;
+            let result = conn
+                .prepare_cached(GET_TAG_PAGE)?
+                .query_map(params![tag, author, page_num * PAGE_SIZE], |row| {
+                    Ok(MooseSearch {
+                        page: row.get::<_, usize>(8)? / PAGE_SIZE,
+                        voted: row.get(9)?,
+                        moose: row.try_into()?,
+                    })
+                })?
+                .collect::<Result<Vec<MooseSearch>, rusqlite::Error>>()?;
+            Ok(MooseSearchPage {
+                pages: count.div_ceil(PAGE_SIZE),
+                result,
+            })
+        })
+        .await
+        .unwrap()
+    }
+
+    // only upvotes or no vote for now...
+    async fn upvote_moose(
+        &self,
+        author: AuthenticatedAuthor,
+        moose: String,
+    ) -> Result<bool, Sqlite3Error> {
+        let conn = self.get().await?;
+        let author = Author::from(author);
+        let voted = conn
+            .interact(move |conn| {
+                conn.prepare_cached(INSERT_VOTE).unwrap().execute(params![
+                    author,
+                    moose,
+                    VoteFlag::Up
+                ])
+            })
+            .await
+            .unwrap()?;
+        Ok(voted == 1)
+    }
+
     async fn unvote_moose(
         &self,
         author: AuthenticatedAuthor,
@@ -329,7 +532,7 @@ impl MooseDB<Sqlite3Error> for Pool {
         moose_in: Option<PathBuf>,
         dup_behavior: super::BulkModeDupe,
     ) -> Result<(), Sqlite3Error> {
-        let mut moose_in = match moose_in {
+        let moose_in = match moose_in {
             Some(path) => {
                 let file = BufReader::new(std::fs::File::open(path)?);
                 serde_json::from_reader::<_, Vec<MooseAny>>(file).unwrap()
@@ -339,12 +542,29 @@ impl MooseDB<Sqlite3Error> for Pool {
         .drain(..)
         .map(|m| m.into())
         .collect::<Vec<Moose>>();
+        self.bulk_insert(moose_in, dup_behavior).await
+    }
 
+    async fn bulk_insert(
+        &self,
+        mut moose_in: Vec<Moose>,
+        dup_behavior: super::BulkModeDupe,
+    ) -> Result<(), Sqlite3Error> {
         moose_in.sort_unstable_by_key(|m| m.created);
         let conn = self.get().await?;
         conn.interact(move |conn| {
             let tx = conn.transaction_with_behavior(rusqlite::TransactionBehavior::Immediate)?;
             moose_in.iter().try_for_each(|moose| {
+                if let Some(parent) = &moose.parent
+                    && !tx
+                        .prepare_cached(HAS_MOOSE)?
+                        .query_row([parent], |row| row.get::<_, bool>(0))?
+                {
+                    log::warn!(
+                        "{} is a remix of missing moose {parent}, importing it without a parent.",
+                        moose.name
+                    );
+                }
                 let pm: MooseToSqlParams = moose.into();
                 if let Err(e) = tx
                     .prepare_cached(INSERT_MOOSE_WITH_COMPUTED_POS)
@@ -356,7 +576,9 @@ impl MooseDB<Sqlite3Error> for Pool {
                             BulkModeDupe::Fail => return Err(e),
                             BulkModeDupe::Ignore => (),
                             BulkModeDupe::Update => {
-                                let _ = tx.prepare_cached(UPDATE_MOOSE).unwrap().execute(pm)?;
+                                if tx.prepare_cached(UPDATE_MOOSE).unwrap().execute(pm)? == 0 {
+                                    log::warn!("Not updating deleted moose: {}", moose.name);
+                                }
                             }
                         }
                     } else {
@@ -1,10 +1,13 @@
 use deadpool_sqlite::{Hook, HookError};
 use deadpool_sync::SyncWrapper;
-use rusqlite::Connection;
+use rusqlite::{Connection, TransactionBehavior};
 
 use crate::config::{self, RunConfig};
 
-use super::{query::CREATE_TABLE, sqlite3_impl::Pool};
+use super::{
+    query::{CREATE_TABLE, MOOSE_COLUMN_MIGRATIONS, NEEDS_MOOSE_COLUMN},
+    sqlite3_impl::Pool,
+};
 
 pub async fn open_db(rc: &RunConfig) -> Pool {
     let moose_path = rc.get_moose_path();
@@ -14,23 +17,45 @@ pub async fn open_db(rc: &RunConfig) -> Pool {
         .expect("Expected to build Sqlite3 pool builder.")
         .post_create(Hook::async_fn(|con: &mut SyncWrapper<Connection>, _| {
             Box::pin(async move {
-                con.interact(|con| con.execute_batch(CREATE_TABLE).map_err(HookError::Backend))
-                    .await
-                    .expect("conn.interact should not fail.")
+                con.interact(|con| {
+                    migrate(con)
+                        .and_then(|_| con.execute_batch(CREATE_TABLE))
+                        .map_err(HookError::Backend)
+                })
+                .await
+                .expect("conn.interact should not fail.")
             })
         }))
         .build()
         .unwrap() // only fails when no runtime given.
 }
 
+/// Brings a database made by an older moose2 up to date before CREATE_TABLE runs.
+/// Every connection in the pool runs this, so each step must check it is still needed.
+fn migrate(con: &mut Connection) -> Result<(), rusqlite::Error> {
+    let tx = con.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    for (column, add_column) in MOOSE_COLUMN_MIGRATIONS {
+        if tx.query_row(NEEDS_MOOSE_COLUMN, [column], |row| row.get(0))? {
+            log::info!("Migrating database: adding Moose.{column}");
+            tx.execute(add_column, [])?;
+        }
+    }
+    tx.commit()
+}
+
 /// Escapes a search query similar to how legacy moose does. FTS5 syntax is a bit much.
-pub fn escape_query(q: &str) -> String {
-    let words = q
+/// `tag:foo` terms are split out and returned separately, lowercased.
+pub fn escape_query(q: &str) -> (String, Vec<String>) {
+    let (tags, words): (Vec<&str>, Vec<&str>) = q
         .split_whitespace()
         .filter(|substr| !substr.is_empty())
-        .collect::<Vec<_>>();
+        .partition(|substr| substr.starts_with(TAG_PREFIX) && substr.len() > TAG_PREFIX.len());
+    let tags = tags
+        .into_iter()
+        .map(|tag| tag[TAG_PREFIX.len()..].to_lowercase())
+        .collect();
     let len = words.len();
-    words
+    let words = words
         .into_iter()
         .enumerate()
         .map(|(i, substr)| {
@@ -45,5 +70,269 @@ pub fn escape_query(q: &str) -> String {
             }
         })
         .collect::<Vec<String>>()
-        .join(" ")
+        .join(" ");
+    (words, tags)
+}
+
+const TAG_PREFIX: &str = "tag:";
+
+#[cfg(test)]
+mod tests {
+    use super::{CREATE_TABLE, escape_query, migrate};
+    use crate::{
+        db::query::{
+            DUMP_MOOSE, GET_MOOSE, GET_MOOSE_AT_POS, GET_MOOSE_IDX, GET_MOOSE_PAGE,
+            GET_MOOSE_REVISION, GET_REVISIONS, INSERT_MOOSE_WITH_COMPUTED_POS, INSERT_REVISION,
+            INSERT_TOMBSTONE, INSERT_VOTE, LAST_MOOSE, LEN_MOOSE, SEARCH_MOOSE_PAGE,
+            UPDATE_MOOSE_IMAGE,
+        },
+        model::{
+            PAGE_SIZE,
+            author::Author,
+            dimensions::Dimensions,
+            moose::{Moose, MooseToSqlParams},
+            votes::VoteFlag,
+        },
+    };
+    use rusqlite::{Connection, params};
+    use time::OffsetDateTime;
+
+    fn new_db() -> Connection {
+        let mut con = Connection::open_in_memory().unwrap();
+        migrate(&mut con).unwrap();
+        con.execute_batch(CREATE_TABLE).unwrap();
+        con
+    }
+
+    fn insert(con: &Connection, moose: &Moose) {
+        con.execute(
+            INSERT_MOOSE_WITH_COMPUTED_POS,
+            MooseToSqlParams::from(moose),
+        )
+        .unwrap();
+    }
+
+    /// what MooseDB::edit_moose does.
+    fn edit(con: &mut Connection, name: &str, image: &[u8]) {
+        let tx = con.transaction().unwrap();
+        tx.execute(INSERT_REVISION, params![name, OffsetDateTime::now_utc()])
+            .unwrap();
+        tx.execute(
+            UPDATE_MOOSE_IMAGE,
+            params![name, image, Dimensions::Default],
+        )
+        .unwrap();
+        tx.commit().unwrap();
+    }
+
+    fn names<P: rusqlite::Params>(con: &Connection, sql: &str, params: P) -> Vec<String> {
+        con.prepare(sql)
+            .unwrap()
+            .query_map(params, |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    fn pos_upvotes_image(con: &Connection, name: &str) -> (i64, i64, Vec<u8>) {
+        con.query_row(
+            "SELECT pos, upvotes, image FROM Moose WHERE name = ?",
+            [name],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_escape_query_tags() {
+        assert_eq!(
+            escape_query("big tag:Winter OR tag: moose"),
+            (
+                r#""big" OR "tag:" "moose""#.to_owned(),
+                vec!["winter".to_owned()]
+            )
+        );
+        assert_eq!(
+            escape_query("tag:meme"),
+            (String::new(), vec!["meme".to_owned()])
+        );
+    }
+
+    #[test]
+    fn test_migrate_moose_columns() {
+        let mut con = Connection::open_in_memory().unwrap();
+        // a Moose table from before descriptions.
+        con.execute_batch(
+            "CREATE TABLE Moose
+               ( name TEXT PRIMARY KEY, pos INTEGER NOT NULL, image BLOB NOT NULL
+               , dimensions TEXT NOT NULL, created TEXT NOT NULL
+               , author TEXT DEFAULT NULL, upvotes INTEGER DEFAULT 0
+               ) WITHOUT ROWID;
+             INSERT INTO Moose VALUES ('old', 0, X'', 'Default', '', NULL, 0);",
+        )
+        .unwrap();
+        migrate(&mut con).unwrap();
+        con.execute_batch(CREATE_TABLE).unwrap();
+        // every new connection migrates again.
+        migrate(&mut con).unwrap();
+        con.execute(
+            "INSERT INTO Moose VALUES ('new', 1, X'', 'Default', '', NULL, 0, 'winter mascot', 'old')",
+            [],
+        )
+        .unwrap();
+        let found: String = con
+            .query_row(
+                "SELECT moose_name FROM MooseDescriptionSearch WHERE description MATCH 'mascots'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(found, "new");
+        let old: Option<String> = con
+            .query_row(
+                "SELECT description FROM Moose WHERE name = 'old'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(old, None);
+        let parent: Option<String> = con
+            .query_row("SELECT parent FROM Moose WHERE name = 'new'", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(parent.as_deref(), Some("old"));
+    }
+
+    #[test]
+    fn test_edit_and_revert_keep_pos_and_votes() {
+        let mut con = new_db();
+        insert(
+            &con,
+            &Moose::for_test("first", vec![1], Dimensions::Default),
+        );
+        let moose = Moose {
+            upvotes: 3,
+            ..Moose::for_test("edited", vec![1], Dimensions::Default)
+        };
+        insert(&con, &moose);
+        edit(&mut con, "edited", &[2]);
+        edit(&mut con, "edited", &[3]);
+        assert_eq!(pos_upvotes_image(&con, "edited"), (1, 3, vec![3]));
+
+        let revisions: Vec<i64> = con
+            .prepare(GET_REVISIONS)
+            .unwrap()
+            .query_map(["edited"], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(revisions, vec![0, 1]);
+
+        // a revert is an edit back to the image of an older revision.
+        let original: Moose = con
+            .query_row(GET_MOOSE_REVISION, params!["edited", 0], |row| {
+                row.try_into()
+            })
+            .unwrap();
+        assert_eq!(original.image, vec![1]);
+        edit(&mut con, "edited", &original.image);
+        assert_eq!(pos_upvotes_image(&con, "edited"), (1, 3, vec![1]));
+        let latest: i64 = con
+            .query_row(
+                "SELECT MAX(revision) FROM MooseRevision WHERE moose_name = 'edited'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(latest, 2);
+        let current: Moose = con
+            .query_row(GET_MOOSE, ["edited"], |row| row.try_into())
+            .unwrap();
+        assert_eq!((current.name, current.upvotes), ("edited".to_owned(), 3));
+    }
+
+    #[test]
+    fn test_tombstone_hides_moose_and_keeps_pos() {
+        let con = new_db();
+        // the first page is alpha, winter, m2..m11; the second is winter sun, omega.
+        let mut all = vec!["alpha".to_owned(), "winter".to_owned()];
+        all.extend((2..PAGE_SIZE).map(|i| format!("m{i}")));
+        all.extend(["winter sun".to_owned(), "omega".to_owned()]);
+        for name in &all {
+            insert(&con, &Moose::for_test(name, vec![1], Dimensions::Default));
+        }
+        let now = OffsetDateTime::now_utc();
+        for name in ["winter", "omega"] {
+            assert_eq!(
+                con.execute(INSERT_TOMBSTONE, params![name, now]).unwrap(),
+                1
+            );
+        }
+        let live: Vec<String> = all
+            .iter()
+            .filter(|n| !["winter", "omega"].contains(&n.as_str()))
+            .cloned()
+            .collect();
+
+        let len: usize = con.query_row(LEN_MOOSE, [], |row| row.get(0)).unwrap();
+        assert_eq!(len, all.len());
+        let page = |n: usize| {
+            names(
+                &con,
+                GET_MOOSE_PAGE,
+                params![n * PAGE_SIZE, n * PAGE_SIZE + PAGE_SIZE, Author::Anonymous],
+            )
+        };
+        assert_eq!(page(0), live[..PAGE_SIZE - 1]);
+        assert_eq!(page(1), vec!["winter sun".to_owned()]);
+        let pos: usize = con
+            .query_row(
+                "SELECT pos FROM Moose WHERE name = 'winter sun'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(pos, PAGE_SIZE);
+
+        let found = names(
+            &con,
+            SEARCH_MOOSE_PAGE,
+            params![r#""winter""#, Author::Anonymous, "[]"],
+        );
+        assert_eq!(found, vec!["winter sun".to_owned()]);
+        let mut dumped = names(&con, DUMP_MOOSE, []);
+        dumped.sort();
+        let mut expected = live.clone();
+        expected.sort();
+        assert_eq!(dumped, expected);
+        assert_eq!(names(&con, LAST_MOOSE, []), vec!["winter sun".to_owned()]);
+        // random() draws a pos and, after enough misses, takes the next live moose.
+        assert!(names(&con, GET_MOOSE_AT_POS, [1]).is_empty());
+        assert_eq!(names(&con, GET_MOOSE_IDX, [1]), vec!["m2".to_owned()]);
+        assert!(names(&con, GET_MOOSE_IDX, [all.len() - 1]).is_empty());
+
+        // the name stays taken, and the moose can no longer be voted on or edited.
+        assert!(
+            con.execute(
+                INSERT_MOOSE_WITH_COMPUTED_POS,
+                MooseToSqlParams::from(&Moose::for_test("winter", vec![1], Dimensions::Default)),
+            )
+            .is_err()
+        );
+        let voted = con
+            .execute(
+                INSERT_VOTE,
+                params![Author::Anonymous, "winter", VoteFlag::Up],
+            )
+            .unwrap();
+        assert_eq!(voted, 0);
+        let edited = con
+            .execute(
+                UPDATE_MOOSE_IMAGE,
+                params!["winter", [2u8], Dimensions::Default],
+            )
+            .unwrap();
+        assert_eq!(edited, 0);
+    }
 }
@@ -0,0 +1,62 @@
+/* Copyright (C) 2025  Anthony DeDominic
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use time::OffsetDateTime;
+
+use crate::model::{
+    author::Author,
+    dimensions::Dimensions,
+    moose::{Moose, validate_name},
+};
+
+mod png;
+mod text;
+
+pub use png::import_png;
+pub use text::{import_ansi, import_irc};
+
+#[derive(Debug, thiserror::Error)]
+pub enum ImportError {
+    #[error("Invalid moose name: {0}")]
+    Name(&'static str),
+    #[error("Invalid PNG: {0}")]
+    Png(&'static str),
+    #[error(
+        "Image size {0}x{1} is not a multiple of a default (26x15) or HD (36x22) moose, up to 64x."
+    )]
+    Size(usize, usize),
+    #[error("Invalid text art: {0}")]
+    Text(&'static str),
+    #[error("Text art is {0}x{1} pixels, larger than an HD (36x22) moose.")]
+    TooLarge(usize, usize),
+}
+
+/// Wraps an imported image up as a brand new, anonymous moose.
+pub fn new_moose(name: String, image: Vec<u8>) -> Result<Moose, ImportError> {
+    validate_name(&name).map_err(ImportError::Name)?;
+    let dimensions =
+        Dimensions::from_len(&image).expect("importers to only produce default or HD sized moose.");
+    Ok(Moose {
+        name,
+        image,
+        dimensions,
+        created: OffsetDateTime::now_utc(),
+        author: Author::Anonymous,
+        upvotes: 0,
+        description: None,
+        parent: None,
+    })
+}
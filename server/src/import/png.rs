@@ -0,0 +1,345 @@
+/* Copyright (C) 2025  Anthony DeDominic
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::model::{
+    PIX_FMT_MAX,
+    color::{RGBA, TRANSPARENT, nearest_extended},
+    dimensions::{DEFAULT_SIZE, HD_SIZE},
+};
+
+use super::ImportError;
+
+const PNG_MAGIC: &[u8] = &[0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A];
+/// pixels more transparent than this become TRANSPARENT.
+const ALPHA_THRESHOLD: u8 = 0x80;
+
+const GRAY: u8 = 0;
+const RGB: u8 = 2;
+const INDEXED: u8 = 3;
+const GRAY_ALPHA: u8 = 4;
+const RGB_ALPHA: u8 = 6;
+
+struct Header {
+    width: usize,
+    height: usize,
+    depth: u8,
+    color: u8,
+}
+
+impl Header {
+    fn parse(ihdr: &[u8]) -> Result<Self, ImportError> {
+        let &[
+            w0,
+            w1,
+            w2,
+            w3,
+            h0,
+            h1,
+            h2,
+            h3,
+            depth,
+            color,
+            compression,
+            filter,
+            interlace,
+        ] = ihdr
+        else {
+            return Err(ImportError::Png("IHDR is the wrong length."));
+        };
+        let valid_depth = match color {
+            GRAY => matches!(depth, 1 | 2 | 4 | 8 | 16),
+            INDEXED => matches!(depth, 1 | 2 | 4 | 8),
+            RGB | GRAY_ALPHA | RGB_ALPHA => matches!(depth, 8 | 16),
+            _ => return Err(ImportError::Png("unknown color type.")),
+        };
+        if !valid_depth {
+            return Err(ImportError::Png("invalid bit depth for color type."));
+        }
+        if compression != 0 || filter != 0 {
+            return Err(ImportError::Png("unknown compression or filter method."));
+        }
+        if interlace != 0 {
+            return Err(ImportError::Png("interlaced images are not supported."));
+        }
+        Ok(Header {
+            width: u32::from_be_bytes([w0, w1, w2, w3]) as usize,
+            height: u32::from_be_bytes([h0, h1, h2, h3]) as usize,
+            depth,
+            color,
+        })
+    }
+
+    fn channels(&self) -> usize {
+        match self.color {
+            RGB => 3,
+            GRAY_ALPHA => 2,
+            RGB_ALPHA => 4,
+            _ => 1,
+        }
+    }
+
+    /// bytes per scanline, not including the filter byte.
+    fn stride(&self) -> usize {
+        (self.width * self.channels() * self.depth as usize).div_ceil(8)
+    }
+
+    /// bytes per complete pixel, rounded up to 1, as used by the scanline filters.
+    fn bpp(&self) -> usize {
+        (self.channels() * self.depth as usize / 8).max(1)
+    }
+}
+
+/// Figure out which moose grid the image is drawn on and how many pixels make up a cell.
+/// The cell does not have to be square; our own PNGs use 16x24 cells for instance.
+fn grid_scale(width: usize, height: usize) -> Result<(usize, usize, usize, usize), ImportError> {
+    [DEFAULT_SIZE, HD_SIZE]
+        .into_iter()
+        .find(|&(gw, gh, _)| {
+            width.is_multiple_of(gw)
+                && height.is_multiple_of(gh)
+                && (1..=PIX_FMT_MAX).contains(&(width / gw))
+                && (1..=PIX_FMT_MAX).contains(&(height / gh))
+        })
+        .map(|(gw, gh, _)| (gw, gh, width / gw, height / gh))
+        .ok_or(ImportError::Size(width, height))
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let pa = (p - a as i16).abs();
+    let pb = (p - b as i16).abs();
+    let pc = (p - c as i16).abs();
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+/// Reverses the per scanline filters in place, returning the raw scanlines.
+fn unfilter(data: &mut [u8], stride: usize, bpp: usize) -> Result<Vec<&[u8]>, ImportError> {
+    let mut prev = vec![0u8; stride];
+    for row in data.chunks_exact_mut(stride + 1) {
+        let (filter, line) = row.split_first_mut().expect("scanlines are never empty.");
+        match *filter {
+            0 => (),
+            1 => (bpp..stride).for_each(|i| line[i] = line[i].wrapping_add(line[i - bpp])),
+            2 => (0..stride).for_each(|i| line[i] = line[i].wrapping_add(prev[i])),
+            3 => (0..stride).for_each(|i| {
+                let left = if i >= bpp { line[i - bpp] } else { 0 };
+                line[i] = line[i].wrapping_add(((left as u16 + prev[i] as u16) / 2) as u8);
+            }),
+            4 => (0..stride).for_each(|i| {
+                let (left, upleft) = if i >= bpp {
+                    (line[i - bpp], prev[i - bpp])
+                } else {
+                    (0, 0)
+                };
+                line[i] = line[i].wrapping_add(paeth(left, prev[i], upleft));
+            }),
+            _ => return Err(ImportError::Png("unknown scanline filter type.")),
+        }
+        prev.copy_from_slice(line);
+    }
+    Ok(data.chunks_exact(stride + 1).map(|row| &row[1..]).collect())
+}
+
+/// Read the nth sample of a scanline at its native bit depth.
+fn sample(line: &[u8], nth: usize, depth: u8) -> u16 {
+    match depth {
+        16 => u16::from_be_bytes([line[nth * 2], line[nth * 2 + 1]]),
+        8 => line[nth] as u16,
+        _ => {
+            let bit = nth * depth as usize;
+            let mask = (1u16 << depth) - 1;
+            (line[bit / 8] as u16 >> (8 - depth as usize - bit % 8)) & mask
+        }
+    }
+}
+
+/// Scale a sample at its native bit depth to 8 bits.
+fn to_u8(sample: u16, depth: u8) -> u8 {
+    match depth {
+        16 => (sample >> 8) as u8,
+        8 => sample as u8,
+        _ => (sample * 255 / ((1 << depth) - 1)) as u8,
+    }
+}
+
+fn pixel(
+    hdr: &Header,
+    plte: &[u8],
+    trns: Option<&[u8]>,
+    line: &[u8],
+    x: usize,
+) -> Result<RGBA, ImportError> {
+    let ch = hdr.channels();
+    let raw = |c: usize| sample(line, x * ch + c, hdr.depth);
+    let val = |c: usize| to_u8(raw(c), hdr.depth);
+    // tRNS for gray and truecolor is a single 16bit color key.
+    let keyed = |key: &[u16]| {
+        trns.is_some_and(|trns| {
+            trns.len() == key.len() * 2
+                && key
+                    .iter()
+                    .enumerate()
+                    .all(|(i, &k)| u16::from_be_bytes([trns[i * 2], trns[i * 2 + 1]]) == k)
+        })
+    };
+    Ok(match hdr.color {
+        GRAY => {
+            let a = if keyed(&[raw(0)]) { 0 } else { 0xff };
+            RGBA(val(0), val(0), val(0), a)
+        }
+        RGB => {
+            let a = if keyed(&[raw(0), raw(1), raw(2)]) {
+                0
+            } else {
+                0xff
+            };
+            RGBA(val(0), val(1), val(2), a)
+        }
+        INDEXED => {
+            let idx = raw(0) as usize;
+            let &[r, g, b] = plte.get(idx * 3..idx * 3 + 3).unwrap_or_default() else {
+                return Err(ImportError::Png("palette index out of range."));
+            };
+            let a = trns.and_then(|trns| trns.get(idx)).copied().unwrap_or(0xff);
+            RGBA(r, g, b, a)
+        }
+        GRAY_ALPHA => RGBA(val(0), val(0), val(0), val(1)),
+        _ => RGBA(val(0), val(1), val(2), val(3)),
+    })
+}
+
+/// Decode a PNG drawn on a default or HD moose grid, or an integer multiple of it,
+/// into a moose image; each cell is sampled at its center and mapped to the nearest extended color.
+pub fn import_png(png: &[u8]) -> Result<Vec<u8>, ImportError> {
+    let mut rest = png
+        .strip_prefix(PNG_MAGIC)
+        .ok_or(ImportError::Png("missing PNG signature."))?;
+    let mut header = None;
+    let mut plte: &[u8] = &[];
+    let mut trns = None;
+    let mut idat = vec![];
+    loop {
+        let Some(len) = rest.get(..4) else {
+            return Err(ImportError::Png("truncated chunk."));
+        };
+        let len = u32::from_be_bytes(len.try_into().unwrap()) as usize;
+        let Some(chunk) = rest.get(4..len + 12) else {
+            return Err(ImportError::Png("truncated chunk."));
+        };
+        rest = &rest[len + 12..];
+        let (body, crc) = chunk.split_at(len + 4);
+        if crc32fast::hash(body) != u32::from_be_bytes(crc.try_into().unwrap()) {
+            return Err(ImportError::Png("chunk CRC mismatch."));
+        }
+        let (kind, data) = body.split_at(4);
+        if header.is_none() && kind != b"IHDR" {
+            return Err(ImportError::Png("IHDR is not the first chunk."));
+        }
+        match kind {
+            b"IHDR" => header = Some(Header::parse(data)?),
+            b"PLTE" => plte = data,
+            b"tRNS" => trns = Some(data),
+            b"IDAT" => idat.extend_from_slice(data),
+            b"IEND" => break,
+            // lowercase first letter means the chunk is safe to ignore.
+            kind if kind[0].is_ascii_lowercase() => (),
+            _ => return Err(ImportError::Png("unknown critical chunk.")),
+        }
+    }
+    let hdr = header.expect("loop only exits after IHDR.");
+    if hdr.color == INDEXED && plte.is_empty() {
+        return Err(ImportError::Png("indexed image without PLTE."));
+    }
+    // check the size before inflating anything, so we don't decompress something huge.
+    let (grid_w, grid_h, scale_x, scale_y) = grid_scale(hdr.width, hdr.height)?;
+
+    let expected = hdr.height * (hdr.stride() + 1);
+    let mut data = miniz_oxide::inflate::decompress_to_vec_zlib_with_limit(&idat, expected)
+        .map_err(|_| ImportError::Png("IDAT could not be inflated."))?;
+    if data.len() != expected {
+        return Err(ImportError::Png("IDAT is the wrong size."));
+    }
+    let lines = unfilter(&mut data, hdr.stride(), hdr.bpp())?;
+
+    (0..grid_h)
+        .flat_map(|y| (0..grid_w).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            let line = lines[y * scale_y + scale_y / 2];
+            let color = pixel(&hdr, plte, trns, line, x * scale_x + scale_x / 2)?;
+            Ok(if color.3 < ALPHA_THRESHOLD {
+                TRANSPARENT
+            } else {
+                nearest_extended(color)
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::import_png;
+    use crate::{
+        model::{
+            color::{EXTENDED_COLORS, TRANSPARENT},
+            dimensions::Dimensions,
+            moose::Moose,
+            queries::ImageQuery,
+        },
+        render::moose_png,
+    };
+
+    #[test]
+    fn test_png_round_trip() {
+        let image = (0..Dimensions::HD.width_height().2)
+            .map(|i| (i * 7 % EXTENDED_COLORS.len()) as u8)
+            .collect::<Vec<u8>>();
+        let moose = Moose::for_test("round trip", image.clone(), Dimensions::HD);
+        let opts = ImageQuery {
+            width: Some(3),
+            height: Some(5),
+            untrimmed: true,
+            ..Default::default()
+        };
+        let imported = import_png(&moose_png(&moose, &opts)).unwrap();
+        assert_eq!(imported.len(), image.len());
+        // some extended colors are duplicates of each other, so compare what they look like.
+        imported.iter().zip(image.iter()).for_each(|(&got, &want)| {
+            let (got, want) = (
+                EXTENDED_COLORS[got as usize],
+                EXTENDED_COLORS[want as usize],
+            );
+            assert_eq!(
+                (got.0, got.1, got.2, got.3),
+                (want.0, want.1, want.2, want.3)
+            );
+        });
+    }
+
+    #[test]
+    fn test_png_wrong_size() {
+        let mut image = vec![TRANSPARENT; Dimensions::Default.width_height().2];
+        image[0] = 1;
+        let moose = Moose::for_test("trimmed", image, Dimensions::Default);
+        // a trimmed moose of a single pixel is one 16x24 cell.
+        assert!(import_png(&moose_png(&moose, &ImageQuery::default())).is_err());
+    }
+}
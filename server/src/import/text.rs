@@ -0,0 +1,305 @@
+/* Copyright (C) 2025  Anthony DeDominic
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::model::{
+    color::{RGBA, TRANSPARENT, XTERM16_TO_LEGACY, nearest_extended, xterm256_color},
+    dimensions::{DEFAULT_SIZE, HD_SIZE},
+};
+
+use super::ImportError;
+
+const IRC_COLOR: char = '\x03';
+const IRC_RESET: char = '\x0f';
+/// moose_irc ends with an info line that starts in bold.
+const IRC_BOLD: &str = "\x02";
+const TERM_ESCAPE: char = '\x1b';
+/// moose_term ends with an info line that starts in bold.
+const TERM_BOLD: &str = "\x1b[1m";
+const UPPER_HALF: char = '\u{2580}';
+const LOWER_HALF: char = '\u{2584}';
+const FULL_BLOCK: char = '\u{2588}';
+
+/// Pads imported rows out to the smallest moose grid that fits them, centering the art.
+fn pad_rows(mut rows: Vec<Vec<u8>>) -> Result<Vec<u8>, ImportError> {
+    let blank = |row: &Vec<u8>| row.iter().all(|&p| p == TRANSPARENT);
+    let bottom = rows.iter().rev().take_while(|row| blank(row)).count();
+    rows.truncate(rows.len() - bottom);
+    let top = rows.iter().take_while(|row| blank(row)).count();
+    rows.drain(..top);
+    rows.iter_mut().for_each(|row| {
+        let trailing = row.iter().rev().take_while(|&&p| p == TRANSPARENT).count();
+        row.truncate(row.len() - trailing);
+    });
+
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let height = rows.len();
+    if width == 0 {
+        return Err(ImportError::Text("no moose found in text."));
+    }
+    let (grid_w, grid_h, total) = [DEFAULT_SIZE, HD_SIZE]
+        .into_iter()
+        .find(|&(gw, gh, _)| width <= gw && height <= gh)
+        .ok_or(ImportError::TooLarge(width, height))?;
+    let (left, top) = ((grid_w - width) / 2, (grid_h - height) / 2);
+    let mut image = vec![TRANSPARENT; total];
+    rows.iter().enumerate().for_each(|(y, row)| {
+        let start = (top + y) * grid_w + left;
+        image[start..start + row.len()].copy_from_slice(row);
+    });
+    Ok(image)
+}
+
+/// Parses up to two digits of a mIRC color code, returning the code and what remains.
+fn irc_code(s: &str) -> (Option<u8>, &str) {
+    let len = s.bytes().take(2).take_while(u8::is_ascii_digit).count();
+    (s[..len].parse().ok(), &s[len..])
+}
+
+fn irc_row(line: &str) -> Vec<u8> {
+    let mut row = vec![];
+    let (mut fg, mut bg) = (TRANSPARENT, TRANSPARENT);
+    let mut rest = line;
+    while let Some(chr) = rest.chars().next() {
+        rest = &rest[chr.len_utf8()..];
+        match chr {
+            IRC_COLOR => match irc_code(rest) {
+                (None, _) => (fg, bg) = (TRANSPARENT, TRANSPARENT),
+                (Some(code), after) => {
+                    fg = code;
+                    rest = after;
+                    if let Some(after) = rest.strip_prefix(',')
+                        && let (Some(code), after) = irc_code(after)
+                    {
+                        bg = code;
+                        rest = after;
+                    }
+                }
+            },
+            IRC_RESET => (fg, bg) = (TRANSPARENT, TRANSPARENT),
+            // bold, italics, etc. do not change what a pixel looks like.
+            chr if chr.is_control() => (),
+            ' ' => row.push(bg),
+            _ => row.push(if bg != TRANSPARENT { bg } else { fg }),
+        }
+    }
+    row
+}
+
+/// Reverses moose_irc, either its text or JSON output; every line is a row of pixels.
+/// Lines split by max_len cannot be told apart from rows, so they are not rejoined.
+pub fn import_irc(text: &str) -> Result<Vec<u8>, ImportError> {
+    let lines = match serde_json::from_str::<Vec<String>>(text) {
+        Ok(lines) => lines,
+        Err(_) => text.lines().map(str::to_owned).collect(),
+    };
+    pad_rows(
+        lines
+            .iter()
+            .filter(|line| !line.starts_with(IRC_BOLD))
+            .map(|line| irc_row(line))
+            .collect(),
+    )
+}
+
+/// Applies SGR parameters, e.g. 0;38;2;255;0;0, to the current colors.
+fn ansi_sgr(params: &str, fg: &mut u8, bg: &mut u8) -> Result<(), ImportError> {
+    let mut params = params
+        .split(';')
+        .map(|p| if p.is_empty() { Ok(0) } else { p.parse::<u8>() })
+        .collect::<Result<Vec<u8>, _>>()
+        .map_err(|_| ImportError::Text("invalid ANSI color parameter."))?
+        .into_iter();
+    while let Some(param) = params.next() {
+        match param {
+            0 => (*fg, *bg) = (TRANSPARENT, TRANSPARENT),
+            30..=37 => *fg = XTERM16_TO_LEGACY[param as usize - 30],
+            90..=97 => *fg = XTERM16_TO_LEGACY[param as usize - 82],
+            40..=47 => *bg = XTERM16_TO_LEGACY[param as usize - 40],
+            100..=107 => *bg = XTERM16_TO_LEGACY[param as usize - 92],
+            39 => *fg = TRANSPARENT,
+            49 => *bg = TRANSPARENT,
+            38 | 48 => {
+                let color = match (params.next(), params.next()) {
+                    (Some(2), Some(r)) => match (params.next(), params.next()) {
+                        (Some(g), Some(b)) => nearest_extended(RGBA(r, g, b, 0xff)),
+                        _ => return Err(ImportError::Text("truncated ANSI truecolor.")),
+                    },
+                    (Some(5), Some(code @ 0..16)) => XTERM16_TO_LEGACY[code as usize],
+                    (Some(5), Some(code)) => nearest_extended(xterm256_color(code)),
+                    _ => return Err(ImportError::Text("unknown ANSI color mode.")),
+                };
+                if param == 38 {
+                    *fg = color;
+                } else {
+                    *bg = color;
+                }
+            }
+            // bold, italics, etc. do not change what a pixel looks like.
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+/// Returns the top and bottom pixels of every character in the line.
+fn ansi_row(line: &str) -> Result<Vec<(u8, u8)>, ImportError> {
+    let mut row = vec![];
+    let (mut fg, mut bg) = (TRANSPARENT, TRANSPARENT);
+    let mut rest = line;
+    while let Some(chr) = rest.chars().next() {
+        rest = &rest[chr.len_utf8()..];
+        match chr {
+            TERM_ESCAPE => {
+                let Some(csi) = rest.strip_prefix('[') else {
+                    return Err(ImportError::Text("unknown ANSI escape sequence."));
+                };
+                let Some(end) = csi.find(|c| matches!(c, '\x40'..='\x7e')) else {
+                    return Err(ImportError::Text("unterminated ANSI escape sequence."));
+                };
+                if csi[end..].starts_with('m') {
+                    ansi_sgr(&csi[..end], &mut fg, &mut bg)?;
+                }
+                rest = &csi[end + 1..];
+            }
+            chr if chr.is_control() => (),
+            ' ' => row.push((bg, bg)),
+            UPPER_HALF => row.push((fg, bg)),
+            LOWER_HALF => row.push((bg, fg)),
+            FULL_BLOCK => row.push((fg, fg)),
+            _ => {
+                let pix = if fg != TRANSPARENT { fg } else { bg };
+                row.push((pix, pix));
+            }
+        }
+    }
+    Ok(row)
+}
+
+/// Reverses moose_term, including its half block mode, which is used if any half blocks are found.
+pub fn import_ansi(text: &str) -> Result<Vec<u8>, ImportError> {
+    let half = text.contains([UPPER_HALF, LOWER_HALF]);
+    let rows = text
+        .lines()
+        .filter(|line| !line.starts_with(TERM_BOLD))
+        .map(ansi_row)
+        .collect::<Result<Vec<_>, _>>()?;
+    pad_rows(if half {
+        rows.into_iter()
+            .flat_map(|row| {
+                let (top, bottom) = row.into_iter().unzip();
+                [top, bottom]
+            })
+            .collect()
+    } else {
+        rows.into_iter()
+            .map(|row| row.into_iter().map(|(top, _)| top).collect())
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{import_ansi, import_irc};
+    use crate::{
+        model::{
+            color::{EXTENDED_COLORS, EXTENDED_TO_LEGACY, TRANSPARENT},
+            dimensions::{DEFAULT_SIZE, Dimensions},
+            moose::Moose,
+            queries::{IrcColors, IrcQuery, TermQuery, TextFormat},
+        },
+        render::{moose_irc, moose_term},
+    };
+
+    /// A moose that fills its whole grid, so trimming and centering are no-ops.
+    fn full_moose() -> Moose {
+        let image = (0..DEFAULT_SIZE.2)
+            .map(|i| {
+                let (x, y) = (i % DEFAULT_SIZE.0, i / DEFAULT_SIZE.0);
+                if x % 5 == 2 && y % 3 == 1 {
+                    TRANSPARENT
+                } else {
+                    ((i * 13) % TRANSPARENT as usize) as u8
+                }
+            })
+            .collect();
+        Moose {
+            upvotes: 3,
+            ..Moose::for_test("full", image, Dimensions::Default)
+        }
+    }
+
+    fn assert_looks_same(got: &[u8], want: &[u8]) {
+        assert_eq!(got.len(), want.len());
+        got.iter().zip(want.iter()).for_each(|(&got, &want)| {
+            let (g, w) = (
+                EXTENDED_COLORS[got as usize],
+                EXTENDED_COLORS[want as usize],
+            );
+            assert_eq!((g.0, g.1, g.2, g.3), (w.0, w.1, w.2, w.3));
+        });
+    }
+
+    #[test]
+    fn test_irc_round_trip() {
+        let moose = full_moose();
+        let irc = moose_irc(&moose, &IrcQuery::default());
+        assert_eq!(
+            import_irc(str::from_utf8(&irc).unwrap()).unwrap(),
+            moose.image
+        );
+
+        let json = IrcQuery {
+            colors: IrcColors::Legacy,
+            format: TextFormat::Json,
+            ..Default::default()
+        };
+        let irc = moose_irc(&moose, &json);
+        let legacy = moose
+            .image
+            .iter()
+            .map(|&pix| EXTENDED_TO_LEGACY[pix as usize])
+            .collect::<Vec<u8>>();
+        assert_eq!(import_irc(str::from_utf8(&irc).unwrap()).unwrap(), legacy);
+    }
+
+    #[test]
+    fn test_ansi_round_trip() {
+        let moose = full_moose();
+        [false, true].into_iter().for_each(|half| {
+            let term = moose_term(
+                &moose,
+                &TermQuery {
+                    half,
+                    ..Default::default()
+                },
+            );
+            let image = import_ansi(str::from_utf8(&term).unwrap()).unwrap();
+            assert_looks_same(&image, &moose.image);
+        });
+    }
+
+    #[test]
+    fn test_text_padding() {
+        // a 2x2 moose in the top left is centered in a default grid.
+        let image = import_irc("\x034,4@@\n\x033,3@\x03 \n").unwrap();
+        let (width, height, total) = DEFAULT_SIZE;
+        assert_eq!(image.len(), total);
+        let (left, top) = ((width - 2) / 2, (height - 2) / 2);
+        assert_eq!(image[top * width + left..][..2], [4, 4]);
+        assert_eq!(image[(top + 1) * width + left..][..2], [3, TRANSPARENT]);
+        assert_eq!(image.iter().filter(|&&p| p != TRANSPARENT).count(), 3);
+    }
+}
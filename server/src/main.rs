@@ -16,16 +16,21 @@
 
 // use moosedb::MooseDb;
 use crate::{
-    config::SubComm,
-    model::moose::moose_bulk_transform,
+    config::{ConvertTo, SubComm},
+    model::{
+        moose::{moose_bulk_transform, moose_bulk_transform_legacy},
+        queries::ArtFormat,
+    },
     task::{dump_moose_task, shutdown_task, web_task},
 };
 
 use db::MooseDB;
+use std::io::Read;
 use tokio_util::sync::CancellationToken;
 
 pub mod config;
 pub mod db;
+pub mod import;
 pub mod middleware;
 pub mod model;
 pub mod render;
@@ -156,14 +161,22 @@ fn svc_main() -> Result<(), &'static str> {
 
 fn real_main(stop_token: CancellationToken, win_service: bool) -> Result<(), config::ArgsError> {
     let (subcmd, rc) = config::parse_args()?;
-    if let SubComm::Convert(io) = subcmd {
+    if let SubComm::Convert(convert_to, io) = subcmd {
         // We do not need the runtime or database for this
-        log::info!("Converting moose-legacy format to moose2 format.");
         let (moose_in, moose_out) = match io {
             Some((i, o)) => (Some(i), o),
             None => (None, None),
         };
-        moose_bulk_transform(moose_in, moose_out);
+        match convert_to {
+            ConvertTo::Moose2 => {
+                log::info!("Converting moose-legacy format to moose2 format.");
+                moose_bulk_transform(moose_in, moose_out);
+            }
+            ConvertTo::Legacy => {
+                log::info!("Converting moose2 format to moose-legacy format.");
+                moose_bulk_transform_legacy(moose_in, moose_out);
+            }
+        }
         return Ok(());
     }
 
@@ -189,6 +202,44 @@ fn real_main(stop_token: CancellationToken, win_service: bool) -> Result<(), con
             return Ok(());
         }
 
+        if let SubComm::ImportPng(dup_behavior, Some(name), png_in) = subcmd {
+            log::info!("Importing {name} from PNG. Shutting down after importing.");
+            let png = match png_in {
+                Some(path) => std::fs::read(path)?,
+                None => {
+                    let mut png = vec![];
+                    std::io::stdin().lock().read_to_end(&mut png)?;
+                    png
+                }
+            };
+            let moose = import::new_moose(name, import::import_png(&png)?)?;
+            db.bulk_insert(vec![moose], dup_behavior).await?;
+            return Ok(());
+        }
+
+        if let SubComm::ImportArt(dup_behavior, format, name, art_in) = subcmd {
+            log::info!("Importing {name} from text art. Shutting down after importing.");
+            let art = match art_in {
+                Some(path) => std::fs::read_to_string(path)?,
+                None => std::io::read_to_string(std::io::stdin().lock())?,
+            };
+            let image = match format {
+                ArtFormat::Irc => import::import_irc(&art)?,
+                ArtFormat::Ansi => import::import_ansi(&art)?,
+            };
+            db.bulk_insert(vec![import::new_moose(name, image)?], dup_behavior)
+                .await?;
+            return Ok(());
+        }
+
+        if let SubComm::Delete(Some(name)) = subcmd {
+            log::info!("Deleting {name}. Shutting down after deleting.");
+            if !db.delete_moose(&name).await? {
+                return Err(config::ArgsError::NoMoose(name));
+            }
+            return Ok(());
+        }
+
         // make sure our DB actually works and we can open it (no permission issues for instance).
         db.check_pool().await?;
 
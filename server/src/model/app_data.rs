@@ -39,6 +39,8 @@ pub struct AppData {
     pub db: Pool,
     pub cookie_key: Key,
     pub oauth2_client: Option<Oa>,
+    /// GitHub users allowed to delete any moose.
+    pub admins: Vec<String>,
 }
 
 pub struct Oa {
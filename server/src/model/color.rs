@@ -254,3 +254,167 @@ pub const SHADE_TO_EXTENDED: [u8; 119] = [
     91,
     95,
 ];
+
+/// Weighted euclidean distance, a cheap approximation of how different two colors look.
+/// SEE: https://www.compuphase.com/cmetric.htm
+pub const fn color_distance(RGBA(r1, g1, b1, _): RGBA, RGBA(r2, g2, b2, _): RGBA) -> u32 {
+    let rmean = (r1 as i32 + r2 as i32) / 2;
+    let r = r1 as i32 - r2 as i32;
+    let g = g1 as i32 - g2 as i32;
+    let b = b1 as i32 - b2 as i32;
+    ((((512 + rmean) * r * r) >> 8) + 4 * g * g + (((767 - rmean) * b * b) >> 8)) as u32
+}
+
+/// Finds the extended color code that looks closest to an arbitrary color; never TRANSPARENT.
+pub fn nearest_extended(color: RGBA) -> u8 {
+    EXTENDED_COLORS[..TRANSPARENT as usize]
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, ext)| color_distance(color, **ext))
+        .map(|(code, _)| code as u8)
+        .expect("EXTENDED_COLORS is not empty.")
+}
+
+/// Maps every extended color to its nearest legacy mIRC color, codes 0..16.
+pub const EXTENDED_TO_LEGACY: [u8; EXTENDED_COLORS.len()] = {
+    let mut map = [0u8; EXTENDED_COLORS.len()];
+    let mut i = 0usize;
+    while i < EXTENDED_COLORS.len() {
+        let mut best = 0usize;
+        let mut code = 1usize;
+        while code < 16 {
+            if color_distance(EXTENDED_COLORS[i], EXTENDED_COLORS[code])
+                < color_distance(EXTENDED_COLORS[i], EXTENDED_COLORS[best])
+            {
+                best = code;
+            }
+            code += 1;
+        }
+        map[i] = best as u8;
+        i += 1;
+    }
+    map[TRANSPARENT as usize] = TRANSPARENT;
+    map
+};
+
+/// Maps every legacy mIRC color, codes 0..16, to its nearest color in the extended range, codes 16..99.
+/// Legacy moose cannot store codes below 16 in their extended color format.
+pub const LEGACY_TO_EXTENDED: [u8; 16] = {
+    let mut map = [0u8; 16];
+    let mut i = 0usize;
+    while i < 16 {
+        let mut best = 16usize;
+        let mut code = 17usize;
+        while code < TRANSPARENT as usize {
+            if color_distance(EXTENDED_COLORS[i], EXTENDED_COLORS[code])
+                < color_distance(EXTENDED_COLORS[i], EXTENDED_COLORS[best])
+            {
+                best = code;
+            }
+            code += 1;
+        }
+        map[i] = best as u8;
+        i += 1;
+    }
+    map
+};
+
+/// Maps every extended color to the extended color nearest its inverse; transparent stays transparent.
+pub const EXTENDED_INVERTED: [u8; EXTENDED_COLORS.len()] = {
+    let mut map = [0u8; EXTENDED_COLORS.len()];
+    let mut i = 0usize;
+    while i < TRANSPARENT as usize {
+        let RGBA(r, g, b, a) = EXTENDED_COLORS[i];
+        let inverse = RGBA(0xff - r, 0xff - g, 0xff - b, a);
+        let mut best = 0usize;
+        let mut code = 1usize;
+        while code < TRANSPARENT as usize {
+            if color_distance(inverse, EXTENDED_COLORS[code])
+                < color_distance(inverse, EXTENDED_COLORS[best])
+            {
+                best = code;
+            }
+            code += 1;
+        }
+        map[i] = best as u8;
+        i += 1;
+    }
+    map[TRANSPARENT as usize] = TRANSPARENT;
+    map
+};
+
+/// The xterm system colors, codes 0..16, as the legacy mIRC color they are usually themed like.
+pub const XTERM16_TO_LEGACY: [u8; 16] = [1, 5, 3, 7, 2, 6, 10, 15, 14, 4, 9, 8, 12, 13, 11, 0];
+
+/// The xterm 6x6x6 color cube and grayscale ramp, codes 16..=255.
+pub const fn xterm256_color(code: u8) -> RGBA {
+    const CUBE_LEVELS: [u8; 6] = [0x00, 0x5f, 0x87, 0xaf, 0xd7, 0xff];
+    if code >= 232 {
+        let v = 8 + 10 * (code - 232);
+        RGBA(v, v, v, 0xff)
+    } else {
+        let code = code - 16;
+        RGBA(
+            CUBE_LEVELS[(code / 36) as usize],
+            CUBE_LEVELS[(code / 6 % 6) as usize],
+            CUBE_LEVELS[(code % 6) as usize],
+            0xff,
+        )
+    }
+}
+
+/// Maps every extended color to its nearest xterm-256 color.
+/// Codes 0..16 are skipped, terminals are free to theme them however they want.
+pub const EXTENDED_TO_XTERM256: [u8; EXTENDED_COLORS.len()] = {
+    let mut map = [0u8; EXTENDED_COLORS.len()];
+    let mut i = 0usize;
+    while i < EXTENDED_COLORS.len() {
+        let mut best = 16u8;
+        let mut code = 16u8;
+        loop {
+            if color_distance(EXTENDED_COLORS[i], xterm256_color(code))
+                < color_distance(EXTENDED_COLORS[i], xterm256_color(best))
+            {
+                best = code;
+            }
+            if code == u8::MAX {
+                break;
+            }
+            code += 1;
+        }
+        map[i] = best;
+        i += 1;
+    }
+    map
+};
+
+#[cfg(test)]
+mod tests {
+    use super::{EXTENDED_TO_LEGACY, EXTENDED_TO_XTERM256, TRANSPARENT};
+
+    #[test]
+    fn test_legacy_nearest_color() {
+        // legacy colors are already legacy.
+        (0..16).for_each(|code| assert_eq!(EXTENDED_TO_LEGACY[code], code as u8));
+        assert_eq!(EXTENDED_TO_LEGACY[52], 4); // red
+        assert_eq!(EXTENDED_TO_LEGACY[54], 8); // yellow
+        assert_eq!(EXTENDED_TO_LEGACY[56], 9); // lime
+        assert_eq!(EXTENDED_TO_LEGACY[58], 11); // cyan
+        assert_eq!(EXTENDED_TO_LEGACY[60], 12); // blue
+        assert_eq!(EXTENDED_TO_LEGACY[62], 13); // fuchsia
+        assert_eq!(EXTENDED_TO_LEGACY[88], 1); // blackest -> black
+        assert_eq!(EXTENDED_TO_LEGACY[94], 14); // gray -> grey
+        assert_eq!(EXTENDED_TO_LEGACY[97], 15); // light gray -> lightgrey
+        assert_eq!(EXTENDED_TO_LEGACY[98], 0); // whitest -> white
+        assert_eq!(EXTENDED_TO_LEGACY[TRANSPARENT as usize], TRANSPARENT);
+    }
+
+    #[test]
+    fn test_xterm256_nearest_color() {
+        assert_eq!(EXTENDED_TO_XTERM256[4], 196); // red
+        assert_eq!(EXTENDED_TO_XTERM256[12], 21); // blue
+        assert_eq!(EXTENDED_TO_XTERM256[88], 16); // black
+        assert_eq!(EXTENDED_TO_XTERM256[94], 244); // gray
+        assert_eq!(EXTENDED_TO_XTERM256[98], 231); // white
+    }
+}
@@ -0,0 +1,144 @@
+/* Copyright (C) 2025  Anthony DeDominic
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use serde::{Deserialize, Deserializer};
+
+use super::{
+    color::TRANSPARENT,
+    moose::{validate_description, validate_tags},
+    queries::MooseSize,
+};
+
+/// A batch of drawing operations, e.g.
+/// `{"base": {"moose": "moose"}, "ops": [{"op": "line", "from": [0, 0], "to": [25, 14], "color": 88}]}`
+#[derive(Deserialize)]
+pub struct DrawRequest {
+    #[serde(default)]
+    pub base: DrawBase,
+    #[serde(deserialize_with = "from_draw_ops")]
+    pub ops: Vec<DrawOp>,
+    /// only used when saving the drawing.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// only used when saving the drawing.
+    #[serde(deserialize_with = "from_draw_tags", default)]
+    pub tags: Vec<String>,
+    /// only used when saving the drawing.
+    #[serde(deserialize_with = "from_draw_description", default)]
+    pub description: Option<String>,
+}
+
+/// What the operations draw over.
+#[derive(Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DrawBase {
+    /// a transparent moose of the given size.
+    Blank(MooseSize),
+    /// a copy of an existing moose.
+    Moose(String),
+}
+
+impl Default for DrawBase {
+    fn default() -> Self {
+        DrawBase::Blank(MooseSize::Default)
+    }
+}
+
+/// The gridpaint editor tools; points are [x, y] with 0, 0 at the top left.
+#[derive(Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum DrawOp {
+    /// set a single pixel, like the pencil.
+    Pixel {
+        at: (usize, usize),
+        #[serde(deserialize_with = "from_draw_color")]
+        color: u8,
+    },
+    /// a straight line, or a quadratic bezier curve if a control point is given.
+    Line {
+        from: (usize, usize),
+        to: (usize, usize),
+        #[serde(default)]
+        via: Option<(usize, usize)>,
+        #[serde(deserialize_with = "from_draw_color")]
+        color: u8,
+    },
+    /// flood fill the area of same colored pixels around a point.
+    Bucket {
+        at: (usize, usize),
+        #[serde(deserialize_with = "from_draw_color")]
+        color: u8,
+    },
+    /// swap every pixel of one color for another.
+    Replace {
+        #[serde(deserialize_with = "from_draw_color")]
+        old: u8,
+        #[serde(deserialize_with = "from_draw_color")]
+        new: u8,
+    },
+    /// fill the whole moose with one color.
+    Clear {
+        #[serde(deserialize_with = "from_draw_color", default = "draw_clear_default")]
+        color: u8,
+    },
+    /// grow or crop the moose to another size, keeping the drawing centered.
+    Resize { size: MooseSize },
+}
+
+pub const DRAW_MAX_OPS: usize = 512;
+
+fn draw_clear_default() -> u8 {
+    TRANSPARENT
+}
+
+fn from_draw_color<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u8, D::Error> {
+    u8::deserialize(deserializer).and_then(|color| {
+        if TRANSPARENT < color {
+            Err(serde::de::Error::custom(format!(
+                "Color must be a color code up to {TRANSPARENT}."
+            )))
+        } else {
+            Ok(color)
+        }
+    })
+}
+
+fn from_draw_tags<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    Vec::<String>::deserialize(deserializer)
+        .and_then(|tags| validate_tags(tags).map_err(serde::de::Error::custom))
+}
+
+fn from_draw_description<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    String::deserialize(deserializer).and_then(|description| {
+        validate_description(&description)
+            .map(|_| Some(description))
+            .map_err(serde::de::Error::custom)
+    })
+}
+
+fn from_draw_ops<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<DrawOp>, D::Error> {
+    Vec::<DrawOp>::deserialize(deserializer).and_then(|ops| {
+        if DRAW_MAX_OPS < ops.len() {
+            Err(serde::de::Error::custom(format!(
+                "Too many operations, limit: {DRAW_MAX_OPS}"
+            )))
+        } else {
+            Ok(ops)
+        }
+    })
+}
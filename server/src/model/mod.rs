@@ -18,6 +18,7 @@ pub mod app_data;
 pub mod author;
 pub mod color;
 pub mod dimensions;
+pub mod draw;
 pub mod mime;
 pub mod moose;
 pub mod pages;
@@ -31,3 +32,5 @@ pub const PAGE_SEARCH_LIM: usize = 10;
 // this is for PNG output, technically the line output is variable based on font x-height
 pub const PIX_FMT_WIDTH: usize = 16;
 pub const PIX_FMT_HEIGHT: usize = 24;
+// upper bound for user requested PNG cell sizes.
+pub const PIX_FMT_MAX: usize = 64;
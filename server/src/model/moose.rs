@@ -14,12 +14,17 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
-use super::color::{SHADE_TO_EXTENDED, SHADE_TRNS};
+use super::color::{EXTENDED_COLORS, LEGACY_TO_EXTENDED, RGBA, SHADE_TO_EXTENDED, SHADE_TRNS};
 use super::dimensions::Dimensions;
+use super::queries::GridPalette;
 use super::{author::Author, color::TRANSPARENT};
 use base64::{DecodeError, Engine};
-use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+    de::{SeqAccess, Visitor},
+};
 use std::{
+    fmt,
     io::{BufReader, BufWriter},
     path::PathBuf,
 };
@@ -28,33 +33,107 @@ use time::{
 };
 
 const MOOSE_MAX_NAME_LEN: usize = 64usize;
-
-#[derive(Debug, Deserialize, Serialize, Clone)]
-#[serde(remote = "Self")]
+pub const MOOSE_MAX_TAGS: usize = 8;
+const MOOSE_MAX_TAG_LEN: usize = 32;
+/// short enough to fit on one IRC line after the moose.
+const MOOSE_MAX_DESCRIPTION_LEN: usize = 160;
+/// how far back /moose/{moose_name}/ancestry goes.
+pub const MOOSE_MAX_ANCESTRY: usize = 64;
+
+#[derive(Debug, Serialize, Clone)]
 pub struct Moose {
-    #[serde(deserialize_with = "control_len_bound_string")]
     pub name: String,
-    #[serde(serialize_with = "as_base64", deserialize_with = "from_base64")]
+    #[serde(serialize_with = "as_base64")]
     pub image: Vec<u8>,
     pub dimensions: Dimensions,
-    #[serde(serialize_with = "as_js", deserialize_with = "from_js")]
+    #[serde(serialize_with = "as_js")]
     pub created: OffsetDateTime,
-    #[serde(default = "super::author::default_author")]
     pub author: Author,
-    #[serde(default = "upvote_zeroed")]
     pub upvotes: i64,
+    /// what the moose is about, if the name does not say it all.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// the moose this one is a remix of.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+}
+
+#[cfg(test)]
+impl Moose {
+    /// An anonymous moose with no votes, created at the UNIX epoch.
+    pub fn for_test(name: &str, image: Vec<u8>, dimensions: Dimensions) -> Self {
+        Moose {
+            name: name.to_owned(),
+            image,
+            dimensions,
+            created: OffsetDateTime::UNIX_EPOCH,
+            author: Author::Anonymous,
+            upvotes: 0,
+            description: None,
+            parent: None,
+        }
+    }
 }
 
 fn upvote_zeroed() -> i64 {
     0
 }
 
-impl Serialize for Moose {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+/// The wire format of a Moose.
+/// Dimensions are directly tied to the image, but we cannot validate one against
+/// the other until both are fully deserialized, so we do that in Moose's Deserialize impl.
+#[derive(Deserialize)]
+struct MooseIn {
+    #[serde(deserialize_with = "control_len_bound_string")]
+    name: String,
+    image: MooseImage,
+    dimensions: Dimensions,
+    #[serde(deserialize_with = "from_js")]
+    created: OffsetDateTime,
+    #[serde(default = "super::author::default_author")]
+    author: Author,
+    #[serde(default = "upvote_zeroed")]
+    upvotes: i64,
+    #[serde(deserialize_with = "control_len_bound_description", default)]
+    description: Option<String>,
+    #[serde(deserialize_with = "control_len_bound_parent", default)]
+    parent: Option<String>,
+}
+
+/// Moose.image is either base64 encoded color codes or a grid of rows, see MooseGrid.
+enum MooseImage {
+    Flat(Vec<u8>),
+    Grid(Vec<Vec<GridPixel>>),
+}
+
+impl<'de> Deserialize<'de> for MooseImage {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
-        S: Serializer,
+        D: Deserializer<'de>,
     {
-        Self::serialize(self, serializer)
+        struct ImageVisitor;
+
+        impl<'de> Visitor<'de> for ImageVisitor {
+            type Value = MooseImage;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a base64 string or an array of rows of pixels")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, string: &str) -> Result<Self::Value, E> {
+                from_base64(string).map(MooseImage::Flat)
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut rows = vec![];
+                while let Some(row) = seq.next_element()? {
+                    rows.push(row);
+                }
+                Ok(MooseImage::Grid(rows))
+            }
+        }
+
+        deserializer.deserialize_any(ImageVisitor)
     }
 }
 
@@ -63,98 +142,324 @@ impl<'de> Deserialize<'de> for Moose {
     where
         D: Deserializer<'de>,
     {
-        Self::deserialize(deserializer).and_then(|moose| {
-            // You're probably wondering why we remote = "Self" our Deserializer
-            // we do it because Dimensions is directly tied to image and without
-            // twiddling the structure of the type, we have to fully deserialize
-            // to validate the dimensions match the image.
-            match moose.dimensions {
-                Dimensions::Custom(w, h) => {
-                    if moose.image.len() != w * h {
-                        return Err(serde::de::Error::custom(
-                            "Moose.image length does not match Moose.dimensions.",
-                        ));
-                    }
+        let moose = MooseIn::deserialize(deserializer)?;
+        let image = match moose.image {
+            MooseImage::Flat(image) => image,
+            MooseImage::Grid(rows) => {
+                let (width, height, _) = moose.dimensions.width_height();
+                if rows.len() != height || rows.iter().any(|row| row.len() != width) {
+                    return Err(serde::de::Error::custom(
+                        "Moose.image grid does not match Moose.dimensions.",
+                    ));
                 }
-                Dimensions::Default => {
-                    if !matches!(
-                        Dimensions::from_len(&moose.image),
-                        Some(Dimensions::Default),
-                    ) {
-                        return Err(serde::de::Error::custom(
-                            "Moose.image length is not correct.",
-                        ));
-                    }
+                rows.into_iter().flatten().map(GridPixel::code).collect()
+            }
+        };
+        match moose.dimensions {
+            Dimensions::Custom(w, h) => {
+                if image.len() != w * h {
+                    return Err(serde::de::Error::custom(
+                        "Moose.image length does not match Moose.dimensions.",
+                    ));
                 }
-                Dimensions::HD => {
-                    if !matches!(Dimensions::from_len(&moose.image), Some(Dimensions::HD)) {
-                        return Err(serde::de::Error::custom(
-                            "Moose.image length is not an HD moose.",
-                        ));
-                    }
+            }
+            Dimensions::Default => {
+                if !matches!(Dimensions::from_len(&image), Some(Dimensions::Default)) {
+                    return Err(serde::de::Error::custom(
+                        "Moose.image length is not correct.",
+                    ));
                 }
             }
+            Dimensions::HD => {
+                if !matches!(Dimensions::from_len(&image), Some(Dimensions::HD)) {
+                    return Err(serde::de::Error::custom(
+                        "Moose.image length is not an HD moose.",
+                    ));
+                }
+            }
+        }
 
-            Ok(moose)
+        Ok(Moose {
+            name: moose.name,
+            image,
+            dimensions: moose.dimensions,
+            created: moose.created,
+            author: moose.author,
+            upvotes: moose.upvotes,
+            description: moose.description,
+            parent: moose.parent,
         })
     }
 }
 
+/// A single pixel of a moose grid; a color code, or a #rrggbbaa color from EXTENDED_COLORS.
+#[derive(Clone, Copy)]
+pub enum GridPixel {
+    Code(u8),
+    Hex(u8),
+}
+
+impl GridPixel {
+    pub fn code(self) -> u8 {
+        match self {
+            GridPixel::Code(code) | GridPixel::Hex(code) => code,
+        }
+    }
+
+    fn from_hex(hex: &str) -> Option<u8> {
+        let hex = hex.strip_prefix('#')?;
+        if hex.len() != 8 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).unwrap();
+        let (r, g, b, a) = (channel(0), channel(1), channel(2), channel(3));
+        if a == 0 {
+            return Some(TRANSPARENT);
+        }
+        EXTENDED_COLORS
+            .iter()
+            .position(|&RGBA(er, eg, eb, ea)| (er, eg, eb, ea) == (r, g, b, a))
+            .map(|code| code as u8)
+    }
+}
+
+impl Serialize for GridPixel {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match *self {
+            GridPixel::Code(code) => serializer.serialize_u8(code),
+            GridPixel::Hex(code) => {
+                let RGBA(r, g, b, a) = EXTENDED_COLORS[code as usize];
+                serializer.serialize_str(&format!("#{r:02x}{g:02x}{b:02x}{a:02x}"))
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for GridPixel {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct PixelVisitor;
+
+        impl Visitor<'_> for PixelVisitor {
+            type Value = GridPixel;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a color code from 0 to 99 or a #rrggbbaa extended color")
+            }
+
+            fn visit_u64<E: serde::de::Error>(self, code: u64) -> Result<Self::Value, E> {
+                if code <= TRANSPARENT as u64 {
+                    Ok(GridPixel::Code(code as u8))
+                } else {
+                    Err(E::custom(format!("invalid color code: {code}")))
+                }
+            }
+
+            fn visit_str<E: serde::de::Error>(self, hex: &str) -> Result<Self::Value, E> {
+                GridPixel::from_hex(hex)
+                    .map(GridPixel::Hex)
+                    .ok_or_else(|| E::custom(format!("not an extended color: {hex:?}")))
+            }
+        }
+
+        deserializer.deserialize_any(PixelVisitor)
+    }
+}
+
+/// A moose with its image split into rows, e.g. for /moose/{moose_name}?format=grid
+/// Deserializing it as a Moose works too; width and height are ignored.
+#[derive(Serialize)]
+pub struct MooseGrid<'m> {
+    pub name: &'m str,
+    pub image: Vec<Vec<GridPixel>>,
+    pub dimensions: &'m Dimensions,
+    pub width: usize,
+    pub height: usize,
+    #[serde(serialize_with = "as_js")]
+    pub created: OffsetDateTime,
+    pub author: &'m Author,
+    pub upvotes: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<&'m str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent: Option<&'m str>,
+}
+
+impl<'m> MooseGrid<'m> {
+    pub fn new(moose: &'m Moose, palette: GridPalette) -> Self {
+        let (width, height, _) = moose.dimensions.width_height();
+        let pixel = match palette {
+            GridPalette::Index => GridPixel::Code,
+            GridPalette::Hex => GridPixel::Hex,
+        };
+        MooseGrid {
+            name: &moose.name,
+            image: moose
+                .image
+                .chunks(width)
+                .map(|row| row.iter().copied().map(pixel).collect())
+                .collect(),
+            dimensions: &moose.dimensions,
+            width,
+            height,
+            created: moose.created,
+            author: &moose.author,
+            upvotes: moose.upvotes,
+            description: moose.description.as_deref(),
+            parent: moose.parent.as_deref(),
+        }
+    }
+}
+
+/// A past image of a moose, as listed by /revisions/{moose_name}
+#[derive(Debug, Serialize)]
+pub struct MooseRevision {
+    pub revision: i64,
+    pub dimensions: Dimensions,
+    #[serde(serialize_with = "as_js")]
+    pub replaced: OffsetDateTime,
+}
+
+/// A moose in the lineage of another, as listed by /moose/{moose_name}/ancestry
+#[derive(Debug, Serialize)]
+pub struct MooseAncestor {
+    pub name: String,
+    /// the moose is gone, only its name is left.
+    pub deleted: bool,
+}
+
 fn control_len_bound_string<'de, D: Deserializer<'de>>(
     deserializer: D,
 ) -> Result<String, D::Error> {
     String::deserialize(deserializer).and_then(|name| {
-        if name.is_empty() {
-            return Err(serde::de::Error::custom("Moose.name is empty."));
-        }
+        validate_name(&name)
+            .map(|_| name)
+            .map_err(serde::de::Error::custom)
+    })
+}
 
-        if name.len() > MOOSE_MAX_NAME_LEN {
-            return Err(serde::de::Error::custom(
-                "Moose.name is too long: >64 bytes.",
-            ));
-        }
+/// Checks a moose name is something we are willing to store and serve.
+pub fn validate_name(name: &str) -> Result<(), &'static str> {
+    if name.is_empty() {
+        return Err("Moose.name is empty.");
+    }
 
-        if matches!(name.as_str(), "random" | "latest" | "oldest") {
-            return Err(serde::de::Error::custom(
-                "Moose.name cannot be a reserved word: random | latest | oldest",
-            ));
-        }
+    if name.len() > MOOSE_MAX_NAME_LEN {
+        return Err("Moose.name is too long: >64 bytes.");
+    }
+
+    if matches!(name, "random" | "latest" | "oldest") {
+        return Err("Moose.name cannot be a reserved word: random | latest | oldest");
+    }
+
+    if name.contains(|chr| matches!(chr, '\x00'..='\x1f')) {
+        return Err("Moose.name cannot contain an ASCII control character.");
+    }
+
+    if name != name.trim() {
+        return Err("Moose.name cannot contain leading/trailing whitespace.");
+    }
+
+    Ok(())
+}
+
+fn control_len_bound_description<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    Option::<String>::deserialize(deserializer).and_then(|description| {
+        description
+            .map(|description| validate_description(&description).map(|_| description))
+            .transpose()
+            .map_err(serde::de::Error::custom)
+    })
+}
+
+fn control_len_bound_parent<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    Option::<String>::deserialize(deserializer).and_then(|parent| {
+        parent
+            .map(|parent| validate_name(&parent).map(|_| parent))
+            .transpose()
+            .map_err(serde::de::Error::custom)
+    })
+}
+
+/// Checks a moose description is a single line of plain text we are willing to store and serve.
+pub fn validate_description(description: &str) -> Result<(), &'static str> {
+    if description.is_empty() {
+        return Err("Moose.description is empty.");
+    }
+
+    if description.len() > MOOSE_MAX_DESCRIPTION_LEN {
+        return Err("Moose.description is too long: >160 bytes.");
+    }
 
-        if name.contains(|chr| matches!(chr, '\x00'..='\x1f')) {
-            return Err(serde::de::Error::custom(
-                "Moose.name cannot contain an ASCII control character.",
-            ));
+    // not just ASCII; C1 controls can start terminal escapes too.
+    if description.contains(char::is_control) {
+        return Err("Moose.description cannot contain a control character.");
+    }
+
+    if description != description.trim() {
+        return Err("Moose.description cannot contain leading/trailing whitespace.");
+    }
+
+    Ok(())
+}
+
+/// Tags are lowercase ASCII words, optionally joined by hyphens, e.g. "team-mascot".
+pub fn validate_tag(tag: &str) -> Result<(), &'static str> {
+    if tag.is_empty() {
+        return Err("Tag is empty.");
+    }
+
+    if tag.len() > MOOSE_MAX_TAG_LEN {
+        return Err("Tag is too long: >32 bytes.");
+    }
+
+    tag.split('-').try_for_each(|word| {
+        if word.is_empty() {
+            return Err("Tag can only have hyphens between words.");
         }
 
-        if name != name.trim() {
-            return Err(serde::de::Error::custom(
-                "Moose.name cannot contain leading/trailing whitespace.",
-            ));
+        if word.contains(|c: char| !(c.is_ascii_lowercase() || c.is_ascii_digit())) {
+            return Err("Tag must be lowercase alphanumeric with optional hyphens.");
         }
 
-        Ok(name)
+        Ok(())
     })
 }
 
+/// Validates, sorts and deduplicates a set of tags for a moose.
+pub fn validate_tags(mut tags: Vec<String>) -> Result<Vec<String>, &'static str> {
+    tags.iter().try_for_each(|tag| validate_tag(tag))?;
+    tags.sort_unstable();
+    tags.dedup();
+    if tags.len() > MOOSE_MAX_TAGS {
+        return Err("Too many tags: >8.");
+    }
+    Ok(tags)
+}
+
 fn as_base64<S: Serializer>(image: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
     serializer.serialize_str(&base64::engine::general_purpose::STANDARD.encode(image))
 }
 
-fn from_base64<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
-    String::deserialize(deserializer).and_then(|string| {
-        base64::engine::general_purpose::STANDARD
-            .decode(string)
-            .and_then(|decoded| {
-                if let Some(pos) = decoded.iter().position(
-                    |&b| b > TRANSPARENT, /* anything bigger than Transparent is invalid */
-                ) {
-                    Err(DecodeError::InvalidByte(pos, decoded[pos]))
-                } else {
-                    Ok(decoded)
-                }
-            })
-            .map_err(|err| serde::de::Error::custom(err.to_string()))
-    })
+fn from_base64<E: serde::de::Error>(string: &str) -> Result<Vec<u8>, E> {
+    base64::engine::general_purpose::STANDARD
+        .decode(string)
+        .and_then(|decoded| {
+            if let Some(pos) = decoded.iter().position(
+                |&b| b > TRANSPARENT, /* anything bigger than Transparent is invalid */
+            ) {
+                Err(DecodeError::InvalidByte(pos, decoded[pos]))
+            } else {
+                Ok(decoded)
+            }
+        })
+        .map_err(|err| E::custom(err.to_string()))
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -201,6 +506,46 @@ impl From<MooseLegacy> for Moose {
             created: old.created,
             author: Author::Anonymous,
             upvotes: 0,
+            description: None,
+            parent: None,
+        }
+    }
+}
+
+/// Legacy moose only know default and HD sizes; a custom sized moose is written out
+/// row by row, but the legacy parser will not accept it, so filter them out first.
+/// Legacy mIRC colors, codes 0..16, are swapped for the nearest extended color.
+impl From<&Moose> for MooseLegacy {
+    fn from(moose: &Moose) -> Self {
+        let (width, _, _) = moose.dimensions.width_height();
+        let (image, shade) = moose
+            .image
+            .chunks(width)
+            .map(|row| {
+                row.iter()
+                    .map(|&pix| hexish_color_code(pix))
+                    .unzip::<_, _, String, String>()
+            })
+            .fold(
+                (String::new(), String::new()),
+                |(mut image, mut shade), (irow, srow)| {
+                    if !image.is_empty() {
+                        image.push('\n');
+                        shade.push('\n');
+                    }
+                    image.push_str(&irow);
+                    shade.push_str(&srow);
+                    (image, shade)
+                },
+            );
+        MooseLegacy {
+            name: moose.name.clone(),
+            image,
+            shade,
+            created: moose.created,
+            hd: matches!(moose.dimensions, Dimensions::HD),
+            shaded: true,
+            extended: true,
         }
     }
 }
@@ -254,6 +599,40 @@ pub fn moose_bulk_transform(moose_in: Option<PathBuf>, moose_out: Option<PathBuf
     };
 }
 
+pub fn moose_bulk_transform_legacy(moose_in: Option<PathBuf>, moose_out: Option<PathBuf>) {
+    let moose_in = match moose_in {
+        Some(path) => {
+            let file = BufReader::new(std::fs::File::open(path).unwrap());
+            serde_json::from_reader::<_, Vec<Moose>>(file).unwrap()
+        }
+        None => serde_json::from_reader::<_, Vec<Moose>>(std::io::stdin().lock()).unwrap(),
+    };
+    let meese = moose_in
+        .iter()
+        .filter(|moose| {
+            if let Dimensions::Custom(w, h) = moose.dimensions {
+                log::warn!(
+                    "Skipping {}: custom dimensions {w}x{h} cannot be converted to moose-legacy format.",
+                    moose.name
+                );
+                false
+            } else {
+                true
+            }
+        })
+        .map(|moose| moose.into())
+        .collect::<Vec<MooseLegacy>>();
+    match moose_out {
+        Some(path) => {
+            let file = BufWriter::new(std::fs::File::create(path).unwrap());
+            serde_json::to_writer::<_, Vec<MooseLegacy>>(file, &meese).unwrap()
+        }
+        None => {
+            serde_json::to_writer::<_, Vec<MooseLegacy>>(std::io::stdout().lock(), &meese).unwrap()
+        }
+    };
+}
+
 /// Turn Meese string into byte values.
 /// use flat_map and make sure to map dimension by explicitly defining it.
 fn parse_hexish(hex: u8) -> u8 {
@@ -304,6 +683,20 @@ fn extended_color_code(color: u8, shade: u8) -> Option<u8> {
     }
 }
 
+/// 0..=99 -> IRC Extended Color Code, the inverse of extended_color_code.
+fn hexish_color_code(pixel: u8) -> (char, char) {
+    const HEXISH: &[u8; 16] = b"0123456789abcdef";
+    let pixel = match pixel {
+        TRANSPARENT => return ('t', 't'),
+        0..16 => LEGACY_TO_EXTENDED[pixel as usize],
+        _ => pixel,
+    } - 16;
+    (
+        HEXISH[(pixel % 12) as usize] as char,
+        HEXISH[(pixel / 12) as usize] as char,
+    )
+}
+
 /// SEE: https://tc39.es/ecma262/multipage/numbers-and-dates.html#sec-date-time-string-format
 /// It's a simplified ISO-8601.
 /// YYYY is mandatory.
@@ -348,6 +741,31 @@ impl TryFrom<&rusqlite::Row<'_>> for Moose {
             created: row.get(3)?,
             author: row.get(4)?,
             upvotes: row.get(5)?,
+            description: row.get(6)?,
+            parent: row.get(7)?,
+        })
+    }
+}
+
+impl TryFrom<&rusqlite::Row<'_>> for MooseAncestor {
+    type Error = rusqlite::Error;
+
+    fn try_from(row: &rusqlite::Row<'_>) -> Result<Self, Self::Error> {
+        Ok(MooseAncestor {
+            name: row.get(0)?,
+            deleted: row.get(1)?,
+        })
+    }
+}
+
+impl TryFrom<&rusqlite::Row<'_>> for MooseRevision {
+    type Error = rusqlite::Error;
+
+    fn try_from(row: &rusqlite::Row<'_>) -> Result<Self, Self::Error> {
+        Ok(MooseRevision {
+            revision: row.get(0)?,
+            dimensions: row.get(1)?,
+            replaced: row.get(2)?,
         })
     }
 }
@@ -359,6 +777,8 @@ pub type MooseToSqlParams<'a> = (
     &'a OffsetDateTime,
     &'a Author,
     &'a i64,
+    &'a Option<String>,
+    &'a Option<String>,
 );
 
 impl<'a> From<&'a Moose> for MooseToSqlParams<'a> {
@@ -370,6 +790,128 @@ impl<'a> From<&'a Moose> for MooseToSqlParams<'a> {
             &moose.created,
             &moose.author,
             &moose.upvotes,
+            &moose.description,
+            &moose.parent,
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Moose, MooseAny, MooseGrid, MooseLegacy};
+    use crate::model::{
+        color::{EXTENDED_COLORS, LEGACY_TO_EXTENDED, TRANSPARENT},
+        dimensions::Dimensions,
+        queries::GridPalette,
+    };
+
+    #[test]
+    fn test_legacy_round_trip() {
+        [Dimensions::Default, Dimensions::HD]
+            .into_iter()
+            .for_each(|dimensions| {
+                let moose = Moose::for_test(
+                    "legacy",
+                    (0..dimensions.width_height().2)
+                        .map(|i| (i % EXTENDED_COLORS.len()) as u8)
+                        .collect(),
+                    dimensions.clone(),
+                );
+                let legacy = MooseLegacy::from(&moose);
+                assert_eq!(legacy.hd, matches!(dimensions, Dimensions::HD));
+                assert_eq!(
+                    legacy.image.lines().count(),
+                    dimensions.width_height().1,
+                    "one line per row."
+                );
+
+                // go through JSON, like the old moose bot would.
+                let json = serde_json::to_string(&legacy).unwrap();
+                let back: Moose = serde_json::from_str::<MooseAny>(&json).unwrap().into();
+                assert_eq!(back.name, moose.name);
+                assert_eq!(back.created, moose.created);
+                assert_eq!(
+                    back.dimensions.width_height(),
+                    moose.dimensions.width_height()
+                );
+                back.image
+                    .iter()
+                    .zip(moose.image.iter())
+                    .for_each(|(&got, &want)| {
+                        let want = if want < 16 {
+                            LEGACY_TO_EXTENDED[want as usize]
+                        } else {
+                            want
+                        };
+                        assert_eq!(got, want);
+                    });
+
+                // colors survive a second trip untouched.
+                let again = MooseLegacy::from(&back);
+                assert_eq!((again.image, again.shade), (legacy.image, legacy.shade));
+            });
+    }
+
+    #[test]
+    fn test_grid_round_trip() {
+        let moose = Moose::for_test(
+            "grid",
+            (0..Dimensions::Default.width_height().2)
+                .map(|i| (i % EXTENDED_COLORS.len()) as u8)
+                .collect(),
+            Dimensions::Default,
+        );
+        [GridPalette::Index, GridPalette::Hex]
+            .into_iter()
+            .for_each(|palette| {
+                let json = serde_json::to_string(&MooseGrid::new(&moose, palette)).unwrap();
+                let back = serde_json::from_str::<Moose>(&json).unwrap();
+                back.image
+                    .iter()
+                    .zip(moose.image.iter())
+                    .for_each(|(&got, &want)| {
+                        let (g, w) = (
+                            EXTENDED_COLORS[got as usize],
+                            EXTENDED_COLORS[want as usize],
+                        );
+                        assert_eq!((g.0, g.1, g.2, g.3), (w.0, w.1, w.2, w.3));
+                    });
+            });
+
+        // same number of pixels, but the rows are the wrong way around.
+        let transposed = serde_json::json!({
+            "name": "grid",
+            "image": vec![vec![0; 15]; 26],
+            "dimensions": "Default",
+            "created": "1970-01-01T00:00:00.000Z",
+        });
+        assert!(serde_json::from_value::<Moose>(transposed).is_err());
+    }
+
+    #[test]
+    fn test_legacy_color_encoding() {
+        assert_eq!(super::hexish_color_code(TRANSPARENT), ('t', 't'));
+        assert_eq!(super::hexish_color_code(16), ('0', '0'));
+        assert_eq!(super::hexish_color_code(98), ('a', '6'));
+        // legacy white, black and red have exact extended twins.
+        assert_eq!(LEGACY_TO_EXTENDED[0], 98);
+        assert_eq!(LEGACY_TO_EXTENDED[1], 88);
+        assert_eq!(LEGACY_TO_EXTENDED[4], 52);
+    }
+
+    #[test]
+    fn test_validate_tags() {
+        let tags = ["winter", "meme", "winter", "ascii-art"].map(String::from);
+        assert_eq!(
+            super::validate_tags(tags.to_vec()).unwrap(),
+            ["ascii-art", "meme", "winter"]
+        );
+        for bad in ["", "Meme", "two words", "-meme", "meme-", "a--b"] {
+            assert!(super::validate_tags(vec![bad.to_owned()]).is_err(), "{bad}");
+        }
+        let many = (0..=super::MOOSE_MAX_TAGS)
+            .map(|i| format!("t{i}"))
+            .collect();
+        assert!(super::validate_tags(many).is_err());
+    }
+}
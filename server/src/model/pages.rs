@@ -34,3 +34,19 @@ pub struct MooseSearchPage {
     pub pages: usize,
     pub result: Vec<MooseSearch>,
 }
+
+#[derive(Debug, Serialize)]
+pub struct TagCount {
+    pub tag: String,
+    /// number of moose with this tag.
+    pub count: usize,
+}
+
+/// Where a moose is in a page sprite sheet, in image pixels.
+#[derive(Debug, Serialize)]
+pub struct SpriteRect {
+    pub x: usize,
+    pub y: usize,
+    pub w: usize,
+    pub h: usize,
+}
@@ -16,7 +16,12 @@
 
 use serde::{Deserialize, Deserializer};
 
-use super::PAGE_SEARCH_LIM;
+use super::{
+    PAGE_SEARCH_LIM, PIX_FMT_HEIGHT, PIX_FMT_MAX, PIX_FMT_WIDTH,
+    color::TRANSPARENT,
+    dimensions::Dimensions,
+    moose::{validate_description, validate_tags},
+};
 
 #[derive(Deserialize)]
 pub struct SearchQuery {
@@ -66,6 +71,414 @@ fn page_num_default() -> usize {
     0
 }
 
+/// Output options for /moose/{moose_name}, e.g. ?format=grid&palette=hex
+#[derive(Deserialize, Default)]
+pub struct MooseQuery {
+    #[serde(default)]
+    pub format: MooseFormat,
+    /// only used by the grid format.
+    #[serde(default)]
+    pub palette: GridPalette,
+}
+
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum MooseFormat {
+    /// the image is base64 encoded color codes, row after row.
+    #[default]
+    Base64,
+    /// the image is an array of rows of pixels.
+    Grid,
+}
+
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum GridPalette {
+    /// pixels are color codes, 0..=99.
+    #[default]
+    Index,
+    /// pixels are #rrggbbaa colors.
+    Hex,
+}
+
+/// Options for /compose/{format}?moose=a&moose=b, e.g. &layout=vertical&gap=2
+/// The moose names are repeated query parameters, so they are not part of this struct.
+#[derive(Deserialize, Default)]
+pub struct ComposeQuery {
+    #[serde(default)]
+    pub layout: Layout,
+    /// transparent moose pixels between every moose.
+    #[serde(deserialize_with = "from_compose_gap", default = "compose_gap_default")]
+    pub gap: usize,
+}
+
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum Layout {
+    /// side by side.
+    #[default]
+    Horizontal,
+    /// stacked on top of each other.
+    Vertical,
+}
+
+pub const COMPOSE_MIN_MOOSE: usize = 2;
+pub const COMPOSE_MAX_MOOSE: usize = 4;
+pub const COMPOSE_MAX_GAP: usize = 8;
+
+fn compose_gap_default() -> usize {
+    1
+}
+
+fn from_compose_gap<'de, D: Deserializer<'de>>(deserializer: D) -> Result<usize, D::Error> {
+    usize::deserialize(deserializer).and_then(|gap| {
+        if COMPOSE_MAX_GAP < gap {
+            Err(serde::de::Error::custom(format!(
+                "Gap must be at most {COMPOSE_MAX_GAP}."
+            )))
+        } else {
+            Ok(gap)
+        }
+    })
+}
+
+/// Options for generating a text banner moose, e.g. /banner/img?text=hello&fg=88&bg=99
+#[derive(Deserialize)]
+#[serde(try_from = "BannerQueryIn")]
+pub struct BannerQuery {
+    /// printable ASCII, wrapped on spaces to fit the moose.
+    pub text: String,
+    /// color code of the letters.
+    pub fg: u8,
+    /// color code behind the letters, never the same as fg.
+    pub bg: u8,
+    /// always make an HD moose, even if the text fits a default one.
+    pub hd: bool,
+}
+
+/// The wire format of a BannerQuery; letters the same color as their background cannot be seen.
+#[derive(Deserialize)]
+struct BannerQueryIn {
+    #[serde(deserialize_with = "from_banner_text")]
+    text: String,
+    #[serde(deserialize_with = "from_banner_color", default = "banner_fg_default")]
+    fg: u8,
+    #[serde(deserialize_with = "from_banner_color", default = "banner_bg_default")]
+    bg: u8,
+    #[serde(deserialize_with = "from_flag", default)]
+    hd: bool,
+}
+
+impl TryFrom<BannerQueryIn> for BannerQuery {
+    type Error = &'static str;
+
+    fn try_from(banner: BannerQueryIn) -> Result<Self, Self::Error> {
+        if banner.fg == banner.bg {
+            return Err("fg and bg must be different colors.");
+        }
+        Ok(BannerQuery {
+            text: banner.text,
+            fg: banner.fg,
+            bg: banner.bg,
+            hd: banner.hd,
+        })
+    }
+}
+
+pub const BANNER_MAX_TEXT_LEN: usize = 64;
+
+/// black
+fn banner_fg_default() -> u8 {
+    88
+}
+
+fn banner_bg_default() -> u8 {
+    TRANSPARENT
+}
+
+fn from_banner_text<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    String::deserialize(deserializer).and_then(|text| {
+        if text.trim().is_empty() {
+            Err(serde::de::Error::custom("text is empty"))
+        } else if text.len() > BANNER_MAX_TEXT_LEN {
+            Err(serde::de::Error::custom("text too large"))
+        } else if !text.chars().all(|chr| matches!(chr, ' '..='~')) {
+            Err(serde::de::Error::custom(
+                "text can only contain printable ASCII characters",
+            ))
+        } else {
+            Ok(text)
+        }
+    })
+}
+
+fn from_banner_color<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u8, D::Error> {
+    u8::deserialize(deserializer).and_then(|color| {
+        if TRANSPARENT < color {
+            Err(serde::de::Error::custom(format!(
+                "Color must be a color code up to {TRANSPARENT}."
+            )))
+        } else {
+            Ok(color)
+        }
+    })
+}
+
+/// Resizes a moose to a standard size before it is rendered, e.g. /img/{moose_name}?size=hd&center
+#[derive(Deserialize, Default)]
+pub struct ResampleQuery {
+    /// the size to resample to, the moose is left alone if not given.
+    #[serde(default)]
+    pub size: Option<MooseSize>,
+    /// place the moose in the middle of the new canvas instead of scaling it.
+    #[serde(deserialize_with = "from_flag", default)]
+    pub center: bool,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MooseSize {
+    /// 26x15
+    Default,
+    /// 36x22
+    HD,
+}
+
+impl From<MooseSize> for Dimensions {
+    fn from(size: MooseSize) -> Self {
+        match size {
+            MooseSize::Default => Dimensions::Default,
+            MooseSize::HD => Dimensions::HD,
+        }
+    }
+}
+
+/// Transforms applied to a moose before it is rendered in any format, e.g. /img/{moose_name}?mirror&rotate=90
+/// They are applied in order: mirror, flip, rotate, then invert.
+#[derive(Deserialize, Default)]
+pub struct TransformQuery {
+    /// swap left and right, so the moose faces the other way.
+    #[serde(deserialize_with = "from_flag", default)]
+    pub mirror: bool,
+    /// swap top and bottom.
+    #[serde(deserialize_with = "from_flag", default)]
+    pub flip: bool,
+    /// clockwise rotation in degrees.
+    #[serde(default)]
+    pub rotate: Rotation,
+    /// swap every color for the one nearest its inverse.
+    #[serde(deserialize_with = "from_flag", default)]
+    pub invert: bool,
+}
+
+#[derive(Deserialize, Default, Clone, Copy)]
+pub enum Rotation {
+    #[default]
+    #[serde(rename = "0")]
+    None,
+    #[serde(rename = "90")]
+    Quarter,
+    #[serde(rename = "180")]
+    Half,
+    #[serde(rename = "270")]
+    ThreeQuarter,
+}
+
+/// Rendering options for image output, e.g. /img/{moose_name}?w=8&h=8&grid
+#[derive(Deserialize, Default)]
+pub struct ImageQuery {
+    /// width of a moose pixel in the output image.
+    #[serde(alias = "w", deserialize_with = "from_cell_size", default)]
+    pub width: Option<usize>,
+    /// height of a moose pixel in the output image.
+    #[serde(alias = "h", deserialize_with = "from_cell_size", default)]
+    pub height: Option<usize>,
+    /// make pixels square, using width (or height if only that was given).
+    #[serde(deserialize_with = "from_flag", default)]
+    pub square: bool,
+    /// keep the full canvas instead of trimming transparent borders.
+    #[serde(deserialize_with = "from_flag", default)]
+    pub untrimmed: bool,
+    /// color code used in place of transparency.
+    #[serde(alias = "bg", deserialize_with = "from_color_code", default)]
+    pub background: Option<u8>,
+    /// draw 1px grid lines around every moose pixel.
+    #[serde(deserialize_with = "from_flag", default)]
+    pub grid: bool,
+}
+
+impl ImageQuery {
+    /// The width and height of a single moose pixel.
+    pub fn cell_size(&self) -> (usize, usize) {
+        if self.square {
+            let side = self.width.or(self.height).unwrap_or(PIX_FMT_WIDTH);
+            (side, side)
+        } else {
+            (
+                self.width.unwrap_or(PIX_FMT_WIDTH),
+                self.height.unwrap_or(PIX_FMT_HEIGHT),
+            )
+        }
+    }
+}
+
+fn from_cell_size<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<usize>, D::Error> {
+    usize::deserialize(deserializer).and_then(|size| {
+        if size == 0 || PIX_FMT_MAX < size {
+            Err(serde::de::Error::custom(format!(
+                "Pixel size must be between 1 and {PIX_FMT_MAX}."
+            )))
+        } else {
+            Ok(Some(size))
+        }
+    })
+}
+
+fn from_color_code<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u8>, D::Error> {
+    u8::deserialize(deserializer).and_then(|color| {
+        if TRANSPARENT <= color {
+            Err(serde::de::Error::custom(format!(
+                "Background must be a color code below {TRANSPARENT}."
+            )))
+        } else {
+            Ok(Some(color))
+        }
+    })
+}
+
+/// Rendering options for /term/{moose_name}, e.g. ?half&colors=256
+#[derive(Deserialize, Default)]
+pub struct TermQuery {
+    /// pack two pixel rows into every line using half blocks.
+    #[serde(deserialize_with = "from_flag", default)]
+    pub half: bool,
+    #[serde(default)]
+    pub colors: TermColors,
+    /// add the moose description after the moose info.
+    #[serde(deserialize_with = "from_flag", default)]
+    pub description: bool,
+}
+
+#[derive(Deserialize, Default, Clone, Copy)]
+pub enum TermColors {
+    #[default]
+    #[serde(rename = "truecolor")]
+    TrueColor,
+    #[serde(rename = "256")]
+    Xterm256,
+}
+
+impl TermColors {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            TermColors::TrueColor => "text/ansi-truecolor",
+            TermColors::Xterm256 => "text/ansi-256color",
+        }
+    }
+}
+
+/// Rendering options for /irc/{moose_name}, e.g. ?colors=16&max_len=400&format=json&description
+#[derive(Deserialize, Default)]
+pub struct IrcQuery {
+    #[serde(default)]
+    pub colors: IrcColors,
+    /// maximum bytes per line, not including the line ending.
+    /// Servers count the `:nick!user@host PRIVMSG #channel :` prefix too, so bots should pass
+    /// IRC_MAX_LINE_LEN minus the length of their own prefix.
+    #[serde(deserialize_with = "from_irc_line_len", default)]
+    pub max_len: Option<usize>,
+    #[serde(default)]
+    pub format: TextFormat,
+    /// add the moose description as its own line after the moose info.
+    #[serde(deserialize_with = "from_flag", default)]
+    pub description: bool,
+}
+
+/// lines must at least fit the moose info line: name, author, upvotes and date, or the description.
+pub const IRC_MIN_LINE_LEN: usize = 200;
+/// 512 bytes minus the CR-LF.
+pub const IRC_MAX_LINE_LEN: usize = 510;
+
+fn from_irc_line_len<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<usize>, D::Error> {
+    usize::deserialize(deserializer).and_then(|len| {
+        if !(IRC_MIN_LINE_LEN..=IRC_MAX_LINE_LEN).contains(&len) {
+            Err(serde::de::Error::custom(format!(
+                "Line length must be between {IRC_MIN_LINE_LEN} and {IRC_MAX_LINE_LEN}."
+            )))
+        } else {
+            Ok(Some(len))
+        }
+    })
+}
+
+/// Text renderings can be returned as is, or as a JSON array of lines.
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum TextFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+#[derive(Deserialize, Default, Clone, Copy)]
+pub enum IrcColors {
+    /// mIRC extended colors, codes 0..99.
+    #[default]
+    #[serde(rename = "99")]
+    Extended,
+    /// Only the legacy mIRC colors, codes 0..16.
+    #[serde(rename = "16")]
+    Legacy,
+}
+
+/// Rendering options for /emoji/{moose_name}, e.g. ?blank=%E2%AC%9B
+#[derive(Deserialize, Default)]
+pub struct EmojiQuery {
+    /// what to draw for transparent pixels.
+    #[serde(deserialize_with = "from_blank", default)]
+    blank: Option<String>,
+}
+
+/// An ideographic space is about as wide as an emoji and is not collapsed like a regular space.
+const EMOJI_DEFAULT_BLANK: &str = "\u{3000}";
+/// Enough for an emoji with a variation selector or a short ZWJ sequence.
+const EMOJI_MAX_BLANK_CHARS: usize = 8;
+
+impl EmojiQuery {
+    pub fn blank(&self) -> &str {
+        self.blank.as_deref().unwrap_or(EMOJI_DEFAULT_BLANK)
+    }
+}
+
+fn from_blank<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    String::deserialize(deserializer).and_then(|blank| {
+        if blank.is_empty() {
+            Err(serde::de::Error::custom("blank is empty"))
+        } else if blank.chars().count() > EMOJI_MAX_BLANK_CHARS {
+            Err(serde::de::Error::custom("blank is too long"))
+        } else if blank.contains(char::is_control) {
+            Err(serde::de::Error::custom(
+                "blank cannot contain control characters",
+            ))
+        } else {
+            Ok(Some(blank))
+        }
+    })
+}
+
+/// Allows flags to be given without values, e.g. ?untrimmed&grid
+fn from_flag<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    String::deserialize(deserializer).and_then(|flag| match flag.as_str() {
+        "" | "1" | "true" | "on" => Ok(true),
+        "0" | "false" | "off" => Ok(false),
+        _ => Err(serde::de::Error::custom(format!(
+            "Invalid flag value: {flag:?}"
+        ))),
+    })
+}
+
 // #[derive(Deserialize)]
 // pub struct LoginRedir {
 //     #[serde(default = "redir_default")]
@@ -81,3 +494,63 @@ fn page_num_default() -> usize {
 // fn debug_default() -> bool {
 //     false
 // }
+
+/// The name of a moose made from an upload or from another moose, e.g. ?name=moose&tags=meme,winter&description=...
+#[derive(Deserialize)]
+pub struct ImportQuery {
+    pub name: String,
+    #[serde(deserialize_with = "from_tags", default)]
+    pub tags: Vec<String>,
+    #[serde(deserialize_with = "from_description", default)]
+    pub description: Option<String>,
+}
+
+/// Options for importing colored text art, e.g. ?name=moose&format=irc
+#[derive(Deserialize)]
+pub struct TextImportQuery {
+    pub name: String,
+    pub format: ArtFormat,
+    #[serde(deserialize_with = "from_tags", default)]
+    pub tags: Vec<String>,
+    #[serde(deserialize_with = "from_description", default)]
+    pub description: Option<String>,
+}
+
+/// Tags to give a new moose, e.g. /new?tags=meme,winter
+#[derive(Deserialize, Default)]
+pub struct TagsQuery {
+    #[serde(deserialize_with = "from_tags", default)]
+    pub tags: Vec<String>,
+}
+
+/// Comma separated tags.
+fn from_tags<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    String::deserialize(deserializer).and_then(|tags| {
+        validate_tags(
+            tags.split(',')
+                .map(|tag| tag.trim().to_owned())
+                .filter(|tag| !tag.is_empty())
+                .collect(),
+        )
+        .map_err(serde::de::Error::custom)
+    })
+}
+
+fn from_description<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    String::deserialize(deserializer).and_then(|description| {
+        validate_description(&description)
+            .map(|_| Some(description))
+            .map_err(serde::de::Error::custom)
+    })
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum ArtFormat {
+    /// mIRC color codes, as output by /irc/{moose_name}.
+    Irc,
+    /// ANSI SGR escapes, as output by /term/{moose_name}.
+    Ansi,
+}
@@ -0,0 +1,132 @@
+/* Copyright (C) 2025  Anthony DeDominic
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::{
+    model::{dimensions::Dimensions, queries::BannerQuery},
+    render::font::{GLYPH_ADVANCE, GLYPH_HEIGHT, glyph},
+};
+
+/// one blank row between lines.
+const LINE_ADVANCE: usize = GLYPH_HEIGHT + 1;
+
+/// Greedily wraps words into lines of at most `width` characters.
+/// Words longer than a whole line are split across lines.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = vec![];
+    let mut line = String::new();
+    for mut word in text.split_whitespace() {
+        while !word.is_empty() {
+            let sep = usize::from(!line.is_empty());
+            if line.len() + sep + word.len() <= width {
+                if sep == 1 {
+                    line.push(' ');
+                }
+                line.push_str(word);
+                word = "";
+            } else if line.is_empty() {
+                // text is ASCII only, so any byte index is a char boundary.
+                let (head, tail) = word.split_at(width);
+                lines.push(head.to_owned());
+                word = tail;
+            } else {
+                lines.push(std::mem::take(&mut line));
+            }
+        }
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Draws the banner text centered in the smallest moose it fits, default or HD.
+/// Returns the image as color codes, ready to become a moose.
+pub fn moose_banner(opts: &BannerQuery) -> Result<Vec<u8>, &'static str> {
+    let sizes: &[Dimensions] = if opts.hd {
+        &[Dimensions::HD]
+    } else {
+        &[Dimensions::Default, Dimensions::HD]
+    };
+    let (width, height, lines) = sizes
+        .iter()
+        .find_map(|dim| {
+            let (width, height, _) = dim.width_height();
+            // the last glyph and line do not need their blank spacing.
+            let lines = wrap(&opts.text, (width + 1) / GLYPH_ADVANCE);
+            (lines.len() <= (height + 1) / LINE_ADVANCE).then_some((width, height, lines))
+        })
+        .ok_or("Text does not fit in an HD moose; use fewer or shorter words.")?;
+
+    let mut image = vec![opts.bg; width * height];
+    let top = (height + 1 - lines.len() * LINE_ADVANCE) / 2;
+    lines.iter().enumerate().for_each(|(line_num, line)| {
+        let left = (width + 1 - line.len() * GLYPH_ADVANCE) / 2;
+        let base_y = top + line_num * LINE_ADVANCE;
+        line.chars().enumerate().for_each(|(i, chr)| {
+            let base_x = left + i * GLYPH_ADVANCE;
+            glyph(chr).iter().enumerate().for_each(|(col, bits)| {
+                (0..GLYPH_HEIGHT)
+                    .filter(|row| bits & (1 << row) != 0)
+                    .for_each(|row| image[(base_y + row) * width + base_x + col] = opts.fg);
+            });
+        });
+    });
+    Ok(image)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{moose_banner, wrap};
+    use crate::model::{
+        color::TRANSPARENT,
+        dimensions::{DEFAULT_SIZE, HD_SIZE},
+        queries::BannerQuery,
+    };
+
+    fn banner(text: &str, hd: bool) -> Result<Vec<u8>, &'static str> {
+        moose_banner(&BannerQuery {
+            text: text.to_owned(),
+            fg: 88,
+            bg: TRANSPARENT,
+            hd,
+        })
+    }
+
+    #[test]
+    fn test_banner_wrap() {
+        assert_eq!(wrap("a bc  def", 4), ["a bc", "def"]);
+        assert_eq!(wrap("abcdefghij", 4), ["abcd", "efgh", "ij"]);
+    }
+
+    #[test]
+    fn test_banner_size() {
+        let image = banner("I", false).unwrap();
+        assert_eq!(image.len(), DEFAULT_SIZE.2);
+        // 'I' is a 3 pixel wide bar, centered in the 5 pixel wide glyph.
+        let (width, height, _) = DEFAULT_SIZE;
+        let rows = image.chunks_exact(width).collect::<Vec<&[u8]>>();
+        let top = (height - 7) / 2;
+        assert!(image[..top * width].iter().all(|&p| p == TRANSPARENT));
+        assert_eq!(
+            rows[top][10..16],
+            [TRANSPARENT, 88, 88, 88, TRANSPARENT, TRANSPARENT]
+        );
+
+        assert_eq!(banner("hello world", false).unwrap().len(), HD_SIZE.2);
+        assert_eq!(banner("hi", true).unwrap().len(), HD_SIZE.2);
+        assert!(banner("hello wide world", false).is_err());
+    }
+}
@@ -0,0 +1,172 @@
+/* Copyright (C) 2025  Anthony DeDominic
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::{
+    model::{color::TRANSPARENT, moose::Moose, queries::ImageQuery},
+    render::{
+        font::{GLYPH_ADVANCE, GLYPH_HEIGHT, glyph},
+        helpers::trim_moose,
+        image::{encode_png, rasterize_rows},
+    },
+};
+
+/// Open Graph recommended image size.
+const CARD_WIDTH: usize = 1200;
+const CARD_HEIGHT: usize = 630;
+const CARD_MARGIN: usize = 40;
+/// light gray
+const CARD_BACKGROUND: u8 = 97;
+/// black
+const CARD_TEXT: u8 = 88;
+/// dark gray
+const CARD_SUBTEXT: u8 = 93;
+const NAME_SCALE: usize = 5;
+const INFO_SCALE: usize = 3;
+/// space between the moose, name and info lines, in name pixels.
+const LINE_GAP: usize = 4;
+
+/// A CARD_WIDTH by CARD_HEIGHT canvas of color codes.
+struct Canvas(Vec<u8>);
+
+impl Canvas {
+    fn fill(&mut self, x: usize, y: usize, w: usize, h: usize, color: u8) {
+        (y..y + h).for_each(|y| {
+            let start = y * CARD_WIDTH + x;
+            self.0[start..start + w].fill(color);
+        });
+    }
+
+    /// Draw a line of text centered on y, shrinking the font until it fits between the margins.
+    /// Returns the height of the line.
+    fn text(&mut self, y: usize, text: &str, scale: usize, color: u8) -> usize {
+        let max_width = CARD_WIDTH - CARD_MARGIN * 2;
+        let len = text.chars().count();
+        let scale = (1..=scale)
+            .rev()
+            .find(|scale| len * GLYPH_ADVANCE * scale <= max_width)
+            .unwrap_or(1);
+        let text = text.chars().take(max_width / (GLYPH_ADVANCE * scale));
+        let width = len.min(max_width / (GLYPH_ADVANCE * scale)) * GLYPH_ADVANCE * scale;
+        let start_x = (CARD_WIDTH - width) / 2;
+        text.enumerate().for_each(|(i, chr)| {
+            let base_x = start_x + i * GLYPH_ADVANCE * scale;
+            glyph(chr).iter().enumerate().for_each(|(col, bits)| {
+                (0..GLYPH_HEIGHT)
+                    .filter(|row| bits & (1 << row) != 0)
+                    .for_each(|row| {
+                        self.fill(base_x + col * scale, y + row * scale, scale, scale, color)
+                    });
+            });
+        });
+        GLYPH_HEIGHT * scale
+    }
+}
+
+/// Paint the card: the moose centered above its name, author and upvotes.
+fn draw_card(moose: &Moose) -> Canvas {
+    let mut canvas = Canvas(vec![CARD_BACKGROUND; CARD_WIDTH * CARD_HEIGHT]);
+
+    let info = match moose.author.clone().displayable() {
+        Some(author) => format!("by {author} - {} upvotes", moose.upvotes),
+        None => format!("{} upvotes", moose.upvotes),
+    };
+    let info_y = CARD_HEIGHT - CARD_MARGIN - GLYPH_HEIGHT * INFO_SCALE;
+    let name_y = info_y - (GLYPH_HEIGHT + LINE_GAP) * NAME_SCALE;
+    canvas.text(name_y, &moose.name, NAME_SCALE, CARD_TEXT);
+    canvas.text(info_y, &info, INFO_SCALE, CARD_SUBTEXT);
+
+    // moose pixels are 2:3, like the PNG output; use the largest size that fits above the text.
+    let image = trim_moose(&moose.image, &moose.dimensions);
+    let (dim_x, dim_y) = (image[0].len(), image.len());
+    let (area_w, area_h) = (
+        CARD_WIDTH - CARD_MARGIN * 2,
+        name_y - LINE_GAP * NAME_SCALE - CARD_MARGIN,
+    );
+    let scale = (area_w / (2 * dim_x)).min(area_h / (3 * dim_y)).max(1);
+    let (cell_w, cell_h) = (2 * scale, 3 * scale);
+    let start_x = CARD_WIDTH.saturating_sub(dim_x * cell_w) / 2;
+    let start_y = CARD_MARGIN + area_h.saturating_sub(dim_y * cell_h) / 2;
+    image.iter().enumerate().for_each(|(y, row)| {
+        row.iter()
+            .enumerate()
+            .filter(|(_, pix)| **pix != TRANSPARENT)
+            .for_each(|(x, &pix)| {
+                let (x, y) = (start_x + x * cell_w, start_y + y * cell_h);
+                // custom moose could be larger than the card, even at the smallest size.
+                if x + cell_w <= CARD_WIDTH && y + cell_h <= name_y {
+                    canvas.fill(x, y, cell_w, cell_h, pix)
+                }
+            })
+    });
+    canvas
+}
+
+/// Given a moose, returns a social preview card with the moose centered above its name, author and upvotes.
+pub fn moose_card(moose: &Moose) -> Vec<u8> {
+    let canvas = draw_card(moose);
+    let rows = canvas.0.chunks_exact(CARD_WIDTH).collect::<Vec<&[u8]>>();
+    encode_png(rasterize_rows(
+        rows,
+        &ImageQuery {
+            width: Some(1),
+            height: Some(1),
+            ..Default::default()
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        CARD_BACKGROUND, CARD_HEIGHT, CARD_MARGIN, CARD_SUBTEXT, CARD_TEXT, CARD_WIDTH, draw_card,
+        moose_card,
+    };
+    use crate::model::{color::TRANSPARENT, dimensions::Dimensions, moose::Moose};
+
+    #[test]
+    fn test_card_layout() {
+        let moose = Moose::for_test(
+            "test",
+            vec![4, 4, TRANSPARENT, TRANSPARENT],
+            Dimensions::Custom(2, 2),
+        );
+        let png = moose_card(&moose);
+        // IHDR width and height.
+        assert_eq!(png[16..20], (CARD_WIDTH as u32).to_be_bytes());
+        assert_eq!(png[20..24], (CARD_HEIGHT as u32).to_be_bytes());
+
+        let canvas = draw_card(&moose).0;
+        let rows = canvas.chunks_exact(CARD_WIDTH).collect::<Vec<&[u8]>>();
+        assert_eq!(rows.len(), CARD_HEIGHT);
+        let rows_with = |color| {
+            (0..CARD_HEIGHT)
+                .filter(|&y| rows[y].contains(&color))
+                .collect::<Vec<usize>>()
+        };
+        // the moose is above its name, which is above the info line.
+        let (red, name, info) = (rows_with(4), rows_with(CARD_TEXT), rows_with(CARD_SUBTEXT));
+        assert!(!red.is_empty() && !name.is_empty() && !info.is_empty());
+        assert!(red.last() < name.first() && name.last() < info.first());
+        // the two moose pixels are scaled up to fill most of the space above the text.
+        assert!(red.len() > CARD_HEIGHT / 2);
+        assert!(
+            rows[..CARD_MARGIN]
+                .iter()
+                .chain(&rows[CARD_HEIGHT - CARD_MARGIN..])
+                .all(|row| row.iter().all(|&pix| pix == CARD_BACKGROUND))
+        );
+    }
+}
@@ -0,0 +1,146 @@
+/* Copyright (C) 2025  Anthony DeDominic
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::{
+    model::{
+        author::Author,
+        color::TRANSPARENT,
+        dimensions::Dimensions,
+        moose::Moose,
+        queries::{ComposeQuery, Layout},
+    },
+    render::helpers::trim_moose,
+};
+
+/// Composites the trimmed images of several moose into a single custom sized moose,
+/// side by side or stacked, with every moose centered and padded with transparency.
+pub fn compose_moose(meese: &[Moose], opts: &ComposeQuery) -> Moose {
+    let images = meese
+        .iter()
+        .map(|moose| trim_moose(&moose.image, &moose.dimensions))
+        .collect::<Vec<Vec<&[u8]>>>();
+    let sizes = images
+        .iter()
+        .map(|image| (image[0].len(), image.len()))
+        .collect::<Vec<(usize, usize)>>();
+    let gaps = opts.gap * (images.len() - 1);
+    let (width, height) = match opts.layout {
+        Layout::Horizontal => (
+            sizes.iter().map(|(w, _)| w).sum::<usize>() + gaps,
+            sizes.iter().map(|&(_, h)| h).max().unwrap_or(0),
+        ),
+        Layout::Vertical => (
+            sizes.iter().map(|&(w, _)| w).max().unwrap_or(0),
+            sizes.iter().map(|(_, h)| h).sum::<usize>() + gaps,
+        ),
+    };
+
+    let mut image = vec![TRANSPARENT; width * height];
+    let mut offset = 0;
+    images.iter().zip(sizes.iter()).for_each(|(rows, &(w, h))| {
+        let (left, top) = match opts.layout {
+            Layout::Horizontal => (offset, (height - h) / 2),
+            Layout::Vertical => ((width - w) / 2, offset),
+        };
+        rows.iter().enumerate().for_each(|(y, row)| {
+            let start = (top + y) * width + left;
+            image[start..start + w].copy_from_slice(row);
+        });
+        offset += opts.gap
+            + match opts.layout {
+                Layout::Horizontal => w,
+                Layout::Vertical => h,
+            };
+    });
+
+    Moose {
+        name: meese
+            .iter()
+            .map(|moose| moose.name.as_str())
+            .collect::<Vec<&str>>()
+            .join(" & "),
+        image,
+        dimensions: Dimensions::Custom(width, height),
+        created: meese
+            .iter()
+            .map(|moose| moose.created)
+            .max()
+            .expect("at least one moose to compose."),
+        author: Author::Anonymous,
+        upvotes: 0,
+        description: None,
+        parent: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::compose_moose;
+    use crate::model::{
+        color::TRANSPARENT,
+        dimensions::{DEFAULT_SIZE, Dimensions},
+        moose::Moose,
+        queries::{ComposeQuery, Layout},
+    };
+
+    /// A default moose with a solid block of color in the middle.
+    fn block(color: u8, width: usize, height: usize) -> Moose {
+        let mut image = vec![TRANSPARENT; DEFAULT_SIZE.2];
+        (0..height).for_each(|y| {
+            let start = (y + 3) * DEFAULT_SIZE.0 + 5;
+            image[start..start + width].fill(color);
+        });
+        Moose::for_test(&format!("block {color}"), image, Dimensions::Default)
+    }
+
+    #[test]
+    fn test_compose_layout() {
+        let meese = [block(4, 3, 4), block(9, 2, 2)];
+        let side_by_side = compose_moose(
+            &meese,
+            &ComposeQuery {
+                layout: Layout::Horizontal,
+                gap: 2,
+            },
+        );
+        assert_eq!(side_by_side.name, "block 4 & block 9");
+        assert_eq!(side_by_side.dimensions.width_height(), (3 + 2 + 2, 4, 28));
+        #[rustfmt::skip]
+        assert_eq!(side_by_side.image, [
+            4, 4, 4, 99, 99, 99, 99,
+            4, 4, 4, 99, 99, 9, 9,
+            4, 4, 4, 99, 99, 9, 9,
+            4, 4, 4, 99, 99, 99, 99,
+        ]);
+
+        let stacked = compose_moose(
+            &meese,
+            &ComposeQuery {
+                layout: Layout::Vertical,
+                gap: 0,
+            },
+        );
+        #[rustfmt::skip]
+        assert_eq!(stacked.image, [
+            4, 4, 4,
+            4, 4, 4,
+            4, 4, 4,
+            4, 4, 4,
+            9, 9, 99,
+            9, 9, 99,
+        ]);
+    }
+}
@@ -0,0 +1,219 @@
+/* Copyright (C) 2025  Anthony DeDominic
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::{
+    model::{dimensions::Dimensions, draw::DrawOp},
+    render::resample::centered,
+};
+
+/// steps along a bezier curve, like the gridpaint line tool.
+const BEZIER_STEPS: usize = 200;
+
+#[derive(Debug, thiserror::Error)]
+pub enum DrawError {
+    #[error("Operation {0}: point ({1}, {2}) is outside of the {3}x{4} moose.")]
+    OutOfBounds(usize, usize, usize, usize, usize),
+}
+
+/// Bresenham's line from start to end, both included.
+fn line_points(from: (usize, usize), to: (usize, usize)) -> Vec<(usize, usize)> {
+    let (mut x1, mut y1) = (from.0 as isize, from.1 as isize);
+    let (x2, y2) = (to.0 as isize, to.1 as isize);
+    let (dx, dy) = ((x2 - x1).abs(), (y2 - y1).abs());
+    let (sx, sy) = (if x1 < x2 { 1 } else { -1 }, if y1 < y2 { 1 } else { -1 });
+    let mut err = dx - dy;
+    let mut points = vec![];
+    while !(x1 == x2 && y1 == y2) {
+        points.push((x1 as usize, y1 as usize));
+        let err2 = err * 2;
+        if err2 > -dy {
+            err -= dy;
+            x1 += sx;
+        }
+        if err2 < dx {
+            err += dx;
+            y1 += sy;
+        }
+    }
+    points.push((x2 as usize, y2 as usize));
+    points
+}
+
+/// A quadratic bezier curve from start to end, pulled towards the control point.
+/// The curve stays within the triangle of its three points, so it never leaves the moose.
+fn bezier_points(
+    from: (usize, usize),
+    via: (usize, usize),
+    to: (usize, usize),
+) -> Vec<(usize, usize)> {
+    let point = |t: f64, p1: usize, p2: usize, p3: usize| {
+        let mt = 1.0 - t;
+        (mt * mt * p1 as f64 + 2.0 * mt * t * p2 as f64 + t * t * p3 as f64).round() as usize
+    };
+    (0..=BEZIER_STEPS)
+        .map(|step| step as f64 / BEZIER_STEPS as f64)
+        .map(|t| (point(t, from.0, via.0, to.0), point(t, from.1, via.1, to.1)))
+        .collect()
+}
+
+/// Flood fills the 4-connected area of pixels sharing the color at x, y.
+fn bucket(image: &mut [u8], width: usize, height: usize, at: (usize, usize), color: u8) {
+    let replace = image[at.1 * width + at.0];
+    if replace == color {
+        return;
+    }
+    let mut stack = vec![at];
+    while let Some((x, y)) = stack.pop() {
+        if image[y * width + x] != replace {
+            continue;
+        }
+        image[y * width + x] = color;
+        if y + 1 < height {
+            stack.push((x, y + 1));
+        }
+        if y > 0 {
+            stack.push((x, y - 1));
+        }
+        if x + 1 < width {
+            stack.push((x + 1, y));
+        }
+        if x > 0 {
+            stack.push((x - 1, y));
+        }
+    }
+}
+
+/// Applies drawing operations in order to an image of the given dimensions.
+/// Returns the new image and dimensions, which differ if the moose was resized.
+pub fn draw_moose(
+    mut image: Vec<u8>,
+    mut dimensions: Dimensions,
+    ops: &[DrawOp],
+) -> Result<(Vec<u8>, Dimensions), DrawError> {
+    for (i, op) in ops.iter().enumerate() {
+        let (width, height, _) = dimensions.width_height();
+        let check = |(x, y): (usize, usize)| {
+            if x < width && y < height {
+                Ok((x, y))
+            } else {
+                Err(DrawError::OutOfBounds(i, x, y, width, height))
+            }
+        };
+        match *op {
+            DrawOp::Pixel { at, color } => {
+                let (x, y) = check(at)?;
+                image[y * width + x] = color;
+            }
+            DrawOp::Line {
+                from,
+                to,
+                via,
+                color,
+            } => {
+                let points = match via {
+                    Some(via) => bezier_points(check(from)?, check(via)?, check(to)?),
+                    None => line_points(check(from)?, check(to)?),
+                };
+                points
+                    .into_iter()
+                    .for_each(|(x, y)| image[y * width + x] = color);
+            }
+            DrawOp::Bucket { at, color } => bucket(&mut image, width, height, check(at)?, color),
+            DrawOp::Replace { old, new } => image
+                .iter_mut()
+                .filter(|pix| **pix == old)
+                .for_each(|pix| *pix = new),
+            DrawOp::Clear { color } => image.fill(color),
+            DrawOp::Resize { size } => {
+                let resized = Dimensions::from(size);
+                let (new_width, new_height, _) = resized.width_height();
+                image = centered(&image, (width, height), (new_width, new_height));
+                dimensions = resized;
+            }
+        }
+    }
+    Ok((image, dimensions))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{bezier_points, draw_moose, line_points};
+    use crate::model::{
+        color::TRANSPARENT,
+        dimensions::{DEFAULT_SIZE, Dimensions, HD_SIZE},
+        draw::DrawRequest,
+    };
+
+    #[test]
+    fn test_draw_lines() {
+        assert_eq!(
+            line_points((0, 0), (3, 1)),
+            [(0, 0), (1, 0), (2, 1), (3, 1)]
+        );
+        assert_eq!(line_points((2, 2), (2, 0)), [(2, 2), (2, 1), (2, 0)]);
+        let curve = bezier_points((0, 0), (4, 0), (4, 4));
+        assert_eq!(curve.first(), Some(&(0, 0)));
+        assert_eq!(curve.last(), Some(&(4, 4)));
+        assert!(curve.iter().all(|&(x, y)| x <= 4 && y <= 4));
+    }
+
+    #[test]
+    fn test_draw_ops() {
+        let req: DrawRequest = serde_json::from_str(
+            r#"{"ops": [
+                {"op": "line", "from": [0, 1], "to": [25, 1], "color": 4},
+                {"op": "bucket", "at": [0, 0], "color": 9},
+                {"op": "replace", "old": 4, "new": 5},
+                {"op": "pixel", "at": [25, 14], "color": 88},
+                {"op": "resize", "size": "hd"}
+            ]}"#,
+        )
+        .unwrap();
+        let (image, dimensions) = draw_moose(
+            vec![TRANSPARENT; DEFAULT_SIZE.2],
+            Dimensions::Default,
+            &req.ops,
+        )
+        .unwrap();
+        assert_eq!(dimensions, Dimensions::HD);
+        let (left, top) = (
+            (HD_SIZE.0 - DEFAULT_SIZE.0) / 2,
+            (HD_SIZE.1 - DEFAULT_SIZE.1) / 2,
+        );
+        let pix = |x: usize, y: usize| image[(top + y) * HD_SIZE.0 + left + x];
+        // the line stops the fill from reaching the rest of the moose.
+        assert_eq!((pix(0, 0), pix(25, 0)), (9, 9));
+        assert_eq!((pix(0, 1), pix(25, 1)), (5, 5));
+        assert_eq!((pix(0, 2), pix(25, 14)), (TRANSPARENT, 88));
+        assert_eq!(image[0], TRANSPARENT);
+
+        let req: DrawRequest =
+            serde_json::from_str(r#"{"ops": [{"op": "pixel", "at": [26, 0], "color": 1}]}"#)
+                .unwrap();
+        assert!(
+            draw_moose(
+                vec![TRANSPARENT; DEFAULT_SIZE.2],
+                Dimensions::Default,
+                &req.ops
+            )
+            .is_err()
+        );
+        assert!(
+            serde_json::from_str::<DrawRequest>(r#"{"ops": [{"op": "clear", "color": 100}]}"#)
+                .is_err()
+        );
+    }
+}
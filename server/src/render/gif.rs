@@ -0,0 +1,341 @@
+/* Copyright (C) 2025  Anthony DeDominic
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::collections::HashMap;
+
+use crate::{
+    model::{moose::Moose, queries::ImageQuery},
+    render::image::{Raster, rasterize},
+};
+
+const GIF_MAGIC: &[u8] = b"GIF89a";
+const EXTENSION: u8 = 0x21;
+const GRAPHIC_CONTROL: u8 = 0xF9;
+const IMAGE_DESCRIPTOR: u8 = 0x2C;
+const TRAILER: u8 = 0x3B;
+/// Global color table present with 8 bit color resolution, bottom 3 bits are the table size.
+const GCT_FLAGS: u8 = 0b1111_0000;
+const MAX_SUB_BLOCK: usize = 255;
+const LZW_MAX_WIDTH: u32 = 12;
+
+/// GIF sizes are 16 bit, big cells or composed moose can go past that.
+#[derive(thiserror::Error, Debug)]
+#[error("A {0}x{1} image is too large for a GIF, at most 65535x65535 fits.")]
+pub struct GifTooLarge(usize, usize);
+
+/// Packs variable width codes, least significant bit first.
+struct BitWriter {
+    buf: Vec<u8>,
+    acc: u32,
+    bits: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, width: u32) {
+        self.acc |= (code as u32) << self.bits;
+        self.bits += width;
+        while self.bits >= 8 {
+            self.buf.push(self.acc as u8);
+            self.acc >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.buf.push(self.acc as u8);
+        }
+        self.buf
+    }
+}
+
+/// GIF flavored LZW: codes start at min_code_size + 1 bits and grow up to 12 bits.
+/// Once the code table is full, a clear code is emitted and the table starts over.
+fn lzw_compress(min_code_size: u32, pixels: impl Iterator<Item = u8>) -> Vec<u8> {
+    let clear = 1u16 << min_code_size;
+    let eoi = clear + 1;
+    let mut out = BitWriter {
+        buf: vec![],
+        acc: 0,
+        bits: 0,
+    };
+    let mut table = HashMap::<(u16, u8), u16>::new();
+    let mut width = min_code_size + 1;
+    let mut next = eoi + 1;
+    let mut prefix: Option<u16> = None;
+
+    out.write(clear, width);
+    for pix in pixels {
+        let Some(code) = prefix else {
+            prefix = Some(pix as u16);
+            continue;
+        };
+        if let Some(&found) = table.get(&(code, pix)) {
+            prefix = Some(found);
+            continue;
+        }
+        out.write(code, width);
+        table.insert((code, pix), next);
+        next += 1;
+        // decoders lag one code behind us, so widen once the code we just added needs it.
+        if next - 1 == 1 << width && width < LZW_MAX_WIDTH {
+            width += 1;
+        }
+        if next == 1 << LZW_MAX_WIDTH {
+            out.write(clear, width);
+            table.clear();
+            width = min_code_size + 1;
+            next = eoi + 1;
+        }
+        prefix = Some(pix as u16);
+    }
+    if let Some(code) = prefix {
+        out.write(code, width);
+        // the decoder adds an entry after reading the last code, which may widen the EOI code.
+        if next == 1 << width && width < LZW_MAX_WIDTH {
+            width += 1;
+        }
+    }
+    out.write(eoi, width);
+    out.finish()
+}
+
+/// The GIF color table size exponent, the table has 2^(n+1) entries.
+fn color_table_bits(colors: usize) -> u8 {
+    (0..8).find(|&n| colors <= 2 << n).unwrap_or(7)
+}
+
+pub fn draw_gif(raster: &Raster) -> Result<Vec<u8>, GifTooLarge> {
+    let (Ok(width), Ok(height)) = (u16::try_from(raster.width), u16::try_from(raster.height))
+    else {
+        return Err(GifTooLarge(raster.width, raster.height));
+    };
+    let (width, height) = (width.to_le_bytes(), height.to_le_bytes());
+    let mut buf = Vec::with_capacity(4096);
+    let colors = raster.plte.len() / 3;
+    let table_bits = color_table_bits(colors);
+
+    buf.extend(GIF_MAGIC);
+    // logical screen descriptor
+    buf.extend(width);
+    buf.extend(height);
+    buf.push(GCT_FLAGS | table_bits);
+    buf.extend([0, 0]); // background color index and pixel aspect ratio.
+    // global color table, padded to its power of two size.
+    buf.extend(&raster.plte);
+    buf.resize(buf.len() + ((2 << table_bits) - colors) * 3, 0);
+
+    // there is only one transparent color, always index 0.
+    if raster.trns {
+        buf.extend([EXTENSION, GRAPHIC_CONTROL, 4, 0x01, 0, 0, 0, 0]);
+    }
+
+    buf.push(IMAGE_DESCRIPTOR);
+    buf.extend([0, 0, 0, 0]); // left, top
+    buf.extend(width);
+    buf.extend(height);
+    buf.push(0); // no local color table, not interlaced.
+
+    // LZW needs at least 2 bits, even for 1-bit images.
+    let min_code_size = (table_bits as u32 + 1).max(2);
+    buf.push(min_code_size as u8);
+    lzw_compress(min_code_size, raster.pixels())
+        .chunks(MAX_SUB_BLOCK)
+        .for_each(|block| {
+            buf.push(block.len() as u8);
+            buf.extend(block);
+        });
+    buf.push(0); // block terminator
+
+    buf.push(TRAILER);
+    Ok(buf)
+}
+
+/// Given a moose and rendering options, returns an encoded GIF rendering.
+pub fn moose_gif(moose: &Moose, opts: &ImageQuery) -> Result<Vec<u8>, GifTooLarge> {
+    draw_gif(&rasterize(moose, opts))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LZW_MAX_WIDTH, draw_gif, moose_gif};
+    use crate::{
+        model::{color::TRANSPARENT, dimensions::Dimensions, moose::Moose, queries::ImageQuery},
+        render::image::{Raster, rasterize},
+    };
+    use rand::Rng;
+
+    struct Decoded {
+        width: usize,
+        height: usize,
+        plte: Vec<u8>,
+        trns: bool,
+        pixels: Vec<u8>,
+    }
+
+    fn lzw_decompress(min_code_size: u32, data: &[u8]) -> Vec<u8> {
+        let clear = 1usize << min_code_size;
+        let eoi = clear + 1;
+        let mut table = (0..=eoi).map(|i| vec![i as u8]).collect::<Vec<_>>();
+        let mut width = min_code_size + 1;
+        let mut prev: Option<usize> = None;
+        let mut pos = 0;
+        let mut out = vec![];
+        loop {
+            let code = (0..width as usize).fold(0, |code, bit| {
+                let b = (data[(pos + bit) / 8] >> ((pos + bit) % 8)) & 1;
+                code | (b as usize) << bit
+            });
+            pos += width as usize;
+            if code == clear {
+                table.truncate(eoi + 1);
+                width = min_code_size + 1;
+                prev = None;
+                continue;
+            } else if code == eoi {
+                break;
+            }
+            let entry = if code < table.len() {
+                table[code].clone()
+            } else {
+                assert_eq!(code, table.len(), "code is beyond the next table entry.");
+                let mut entry = table[prev.expect("KwKwK code without a prefix.")].clone();
+                entry.push(entry[0]);
+                entry
+            };
+            out.extend(&entry);
+            if let Some(prev) = prev
+                && table.len() < 1 << LZW_MAX_WIDTH
+            {
+                let mut new = table[prev].clone();
+                new.push(entry[0]);
+                table.push(new);
+            }
+            if table.len() == 1 << width && width < LZW_MAX_WIDTH {
+                width += 1;
+            }
+            prev = Some(code);
+        }
+        out
+    }
+
+    fn decode_gif(gif: &[u8]) -> Decoded {
+        assert_eq!(&gif[..6], b"GIF89a");
+        let u16_at = |i: usize| u16::from_le_bytes([gif[i], gif[i + 1]]) as usize;
+        let (width, height) = (u16_at(6), u16_at(8));
+        assert_eq!(gif[10] & 0x80, 0x80, "expected a global color table.");
+        let colors = 2 << (gif[10] & 0x07);
+        let plte = gif[13..13 + colors * 3].to_vec();
+        let mut pos = 13 + colors * 3;
+        let mut trns = false;
+        if gif[pos] == 0x21 {
+            assert_eq!(&gif[pos..pos + 3], &[0x21, 0xF9, 4]);
+            trns = gif[pos + 3] & 1 == 1;
+            assert_eq!(gif[pos + 6], 0, "transparent index should be 0.");
+            pos += 8;
+        }
+        assert_eq!(gif[pos], 0x2C);
+        assert_eq!((u16_at(pos + 5), u16_at(pos + 7)), (width, height));
+        pos += 10;
+        let min_code_size = gif[pos] as u32;
+        pos += 1;
+        let mut data = vec![];
+        while gif[pos] != 0 {
+            let len = gif[pos] as usize;
+            data.extend(&gif[pos + 1..pos + 1 + len]);
+            pos += 1 + len;
+        }
+        assert_eq!(&gif[pos + 1..], &[0x3B]);
+        Decoded {
+            width,
+            height,
+            plte,
+            trns,
+            pixels: lzw_decompress(min_code_size, &data),
+        }
+    }
+
+    fn test_moose(dimensions: Dimensions, image: Vec<u8>) -> Moose {
+        Moose::for_test("test", image, dimensions)
+    }
+
+    fn assert_roundtrip(moose: &Moose, opts: &ImageQuery) {
+        let raster = rasterize(moose, opts);
+        let decoded = decode_gif(&moose_gif(moose, opts).unwrap());
+        assert_eq!(
+            (decoded.width, decoded.height),
+            (raster.width, raster.height)
+        );
+        assert_eq!(decoded.trns, raster.trns);
+        assert_eq!(&decoded.plte[..raster.plte.len()], &raster.plte);
+        assert_eq!(decoded.pixels, raster.pixels().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_gif_matches_png_raster() {
+        let mut image = vec![TRANSPARENT; 26 * 15];
+        image[26 * 4 + 3..26 * 4 + 9].fill(4);
+        image[26 * 5 + 3..26 * 5 + 9].fill(52);
+        image[26 * 6 + 5] = 1;
+        let moose = test_moose(Dimensions::Default, image);
+        assert_roundtrip(&moose, &ImageQuery::default());
+        assert_roundtrip(
+            &moose,
+            &ImageQuery {
+                untrimmed: true,
+                background: Some(0),
+                grid: true,
+                ..Default::default()
+            },
+        );
+    }
+
+    #[test]
+    fn test_gif_single_color() {
+        let moose = test_moose(Dimensions::Default, vec![TRANSPARENT; 26 * 15]);
+        assert_roundtrip(&moose, &ImageQuery::default());
+    }
+
+    #[test]
+    fn test_gif_lzw_table_reset() {
+        // noise compresses poorly, which forces the code table to fill up and reset.
+        let mut rng = rand::thread_rng();
+        let image = (0..64 * 64)
+            .map(|_| rng.r#gen_range(0..=TRANSPARENT))
+            .collect::<Vec<u8>>();
+        let moose = test_moose(Dimensions::Custom(64, 64), image);
+        assert_roundtrip(
+            &moose,
+            &ImageQuery {
+                width: Some(2),
+                height: Some(1),
+                ..Default::default()
+            },
+        );
+    }
+
+    #[test]
+    fn test_gif_too_large() {
+        let raster = Raster {
+            bitmap: vec![],
+            width: 65536,
+            height: 24,
+            plte: vec![0, 0, 0],
+            trns: false,
+        };
+        assert!(draw_gif(&raster).is_err());
+    }
+}
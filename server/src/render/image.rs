@@ -18,9 +18,9 @@ use miniz_oxide::deflate::CompressionLevel;
 
 use crate::{
     model::{
-        PIX_FMT_HEIGHT, PIX_FMT_WIDTH,
         color::{EXTENDED_COLORS, RGBA, TRANSPARENT},
         moose::Moose,
+        queries::ImageQuery,
     },
     render::helpers::trim_moose,
 };
@@ -30,6 +30,8 @@ const TRNS: &[u8] = &[
     0, 0, 0, 0x01, 0x74, 0x52, 0x4E, 0x53, 0, 0x40, 0xE6, 0xD8, 0x66,
 ];
 const IEND: &[u8] = &[0, 0, 0, 0, 0x49, 0x45, 0x4E, 0x44, 0xAE, 0x42, 0x60, 0x82];
+/// grey, used for grid lines.
+pub(super) const GRID_COLOR: u8 = 14;
 
 /// To reduce the size of the PLTE, we recolor the moose with only the colors actually used.
 fn recolor_image(mut image: Vec<&[u8]>) -> (Vec<Vec<u8>>, Vec<u8>) {
@@ -77,25 +79,37 @@ fn xyrange(sx: usize, ex: usize, sy: usize, ey: usize) -> impl Iterator<Item = (
 }
 
 /// Generate the uncompressed PNG bitmap.
-fn draw_bitmap(image: &[Vec<u8>], dim_x: usize, dim_y: usize) -> Vec<u8> {
-    let width = PIX_FMT_WIDTH * dim_x;
+/// If a grid color is given, every cell is surrounded by 1px lines of that color.
+/// Returns the bitmap and its width and height.
+fn draw_bitmap(
+    image: &[Vec<u8>],
+    dim_x: usize,
+    dim_y: usize,
+    (cell_w, cell_h): (usize, usize),
+    grid: Option<u8>,
+) -> (Vec<u8>, usize, usize) {
+    let line = usize::from(grid.is_some());
+    let width = (cell_w + line) * dim_x + line;
     let filter_width = width + 1;
-    let height = PIX_FMT_HEIGHT * dim_y;
-    let mut bitmap = std::vec::from_elem(
-        0,
-        dim_x * dim_y * PIX_FMT_WIDTH * PIX_FMT_HEIGHT + height, /* filter bits */
-    );
+    let height = (cell_h + line) * dim_y + line;
+    let mut bitmap = std::vec::from_elem(grid.unwrap_or(0), filter_width * height);
+    if grid.is_some() {
+        // reset filter bits.
+        bitmap
+            .iter_mut()
+            .step_by(filter_width)
+            .for_each(|filter| *filter = 0);
+    }
     xyrange(0, dim_x, 0, dim_y)
         .flat_map(|(x, y)| {
             let pixel = image[y][x];
-            let base_y = y * PIX_FMT_HEIGHT;
-            let base_x = x * PIX_FMT_WIDTH;
+            let base_y = y * (cell_h + line) + line;
+            let base_x = x * (cell_w + line) + line;
             // filter bit...
-            (base_y..base_y + PIX_FMT_HEIGHT)
-                .map(move |y| (idx_1dto2d(base_x, y, filter_width) + 1, pixel))
+            (base_y..base_y + cell_h).map(move |y| (idx_1dto2d(base_x, y, filter_width) + 1, pixel))
         })
-        .for_each(|(idx, pixel)| bitmap[idx..idx + PIX_FMT_WIDTH].fill(pixel));
-    bitmap
+        .for_each(|(idx, pixel)| bitmap[idx..idx + cell_w].fill(pixel));
+    (bitmap, width, height)
 }
 
 fn gen_plte(mut palette: Vec<u8>) -> Vec<u8> {
@@ -157,28 +171,89 @@ pub fn draw_png(w: u32, h: u32, plte: Vec<u8>, has_trns: bool, zimg: Vec<u8>) ->
     buf
 }
 
-/// Given a moose, returns an encoded PNG rendering.
-pub fn moose_png(moose: &Moose) -> Vec<u8> {
-    let trimmed = trim_moose(&moose.image, &moose.dimensions);
-    let (dim_x, dim_y) = trimmed
+/// A recolored and scaled moose bitmap, including PNG filter bits, ready to be encoded.
+pub struct Raster {
+    pub bitmap: Vec<u8>,
+    pub width: usize,
+    pub height: usize,
+    /// RGB triplets.
+    pub plte: Vec<u8>,
+    /// if palette index 0 is transparent.
+    pub trns: bool,
+}
+
+impl Raster {
+    /// The bitmap without PNG filter bits.
+    pub fn pixels(&self) -> impl Iterator<Item = u8> {
+        self.bitmap
+            .chunks_exact(self.width + 1)
+            .flat_map(|row| row[1..].iter().copied())
+    }
+}
+
+/// The rows of a moose, trimmed unless untrimmed is requested.
+pub(super) fn moose_rows<'a>(moose: &'a Moose, opts: &ImageQuery) -> Vec<&'a [u8]> {
+    if opts.untrimmed {
+        let dim_x = moose.dimensions.width_height().0;
+        moose.image.chunks_exact(dim_x).collect()
+    } else {
+        trim_moose(&moose.image, &moose.dimensions)
+    }
+}
+
+/// Trim (unless untrimmed is requested), recolor and scale a moose.
+pub fn rasterize(moose: &Moose, opts: &ImageQuery) -> Raster {
+    rasterize_rows(moose_rows(moose, opts), opts)
+}
+
+/// Recolor and scale rows of pixels; there must be at least one pixel.
+pub fn rasterize_rows(image: Vec<&[u8]>, opts: &ImageQuery) -> Raster {
+    let (dim_x, dim_y) = image
         .first()
-        .map(|row| (row.len(), trimmed.len()))
+        .map(|row| (row.len(), image.len()))
         .expect("trim_moose always returns at least one pixel.");
-    let (image, palette) = recolor_image(trimmed);
-    let bitmap = draw_bitmap(&image, dim_x, dim_y);
+    let (image, mut palette) = recolor_image(image);
+    // transparent is always the first color, if used.
+    let trns = match opts.background {
+        Some(bg) if palette[0] == TRANSPARENT => {
+            palette[0] = bg;
+            false
+        }
+        _ => palette[0] == TRANSPARENT,
+    };
+    // the grid gets its own palette entry; we use at most 101 of 256.
+    let grid = opts.grid.then(|| {
+        palette.push(GRID_COLOR);
+        (palette.len() - 1) as u8
+    });
+    let (bitmap, width, height) = draw_bitmap(&image, dim_x, dim_y, opts.cell_size(), grid);
+    Raster {
+        bitmap,
+        width,
+        height,
+        plte: gen_plte(palette),
+        trns,
+    }
+}
+
+/// Given a moose and rendering options, returns an encoded PNG rendering.
+pub fn moose_png(moose: &Moose, opts: &ImageQuery) -> Vec<u8> {
+    encode_png(rasterize(moose, opts))
+}
+
+pub fn encode_png(raster: Raster) -> Vec<u8> {
+    let Raster {
+        bitmap,
+        width,
+        height,
+        plte,
+        trns,
+    } = raster;
     let bitmap = miniz_oxide::deflate::compress_to_vec_zlib(
         &bitmap,
         CompressionLevel::BestCompression as u8,
     );
-    let trns = palette[0] == TRANSPARENT;
-    let plte = gen_plte(palette);
 
     // Create the PNG
-    draw_png(
-        (PIX_FMT_WIDTH * dim_x) as u32,
-        (PIX_FMT_HEIGHT * dim_y) as u32,
-        plte,
-        trns,
-        bitmap,
-    )
+    draw_png(width as u32, height as u32, plte, trns, bitmap)
 }
@@ -14,9 +14,30 @@
  * along with this program.  If not, see <https://www.gnu.org/licenses/>.
  */
 
+mod banner;
+mod card;
+mod compose;
+mod draw;
+mod font;
+mod gif;
 mod helpers;
 mod image;
+mod resample;
+mod sixel;
+mod sprite;
+mod svg;
 mod textual;
+mod transform;
 
+pub use banner::moose_banner;
+pub use card::moose_card;
+pub use compose::compose_moose;
+pub use draw::{DrawError, draw_moose};
+pub use gif::moose_gif;
 pub use image::moose_png;
-pub use textual::{moose_irc, moose_term};
+pub use resample::resample_moose;
+pub use sixel::moose_sixel;
+pub use sprite::SpriteSheet;
+pub use svg::moose_svg;
+pub use textual::{moose_ascii, moose_emoji, moose_irc, moose_term};
+pub use transform::transform_moose;
@@ -0,0 +1,117 @@
+/* Copyright (C) 2025  Anthony DeDominic
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::model::{
+    color::TRANSPARENT, dimensions::Dimensions, moose::Moose, queries::ResampleQuery,
+};
+
+/// Scales the image to width x height, taking the source pixel under the center of every new pixel.
+fn nearest_neighbor(image: &[u8], from: (usize, usize), to: (usize, usize)) -> Vec<u8> {
+    let ((from_w, from_h), (to_w, to_h)) = (from, to);
+    (0..to_h)
+        .flat_map(|y| (0..to_w).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            let src_x = (2 * x + 1) * from_w / (2 * to_w);
+            let src_y = (2 * y + 1) * from_h / (2 * to_h);
+            image[src_y * from_w + src_x]
+        })
+        .collect()
+}
+
+/// Places the image in the middle of a width x height canvas, unscaled.
+/// A larger image is cropped evenly on every side, a smaller one is padded with transparency.
+pub(super) fn centered(image: &[u8], from: (usize, usize), to: (usize, usize)) -> Vec<u8> {
+    let ((from_w, from_h), (to_w, to_h)) = (from, to);
+    let off_x = (to_w as isize - from_w as isize) / 2;
+    let off_y = (to_h as isize - from_h as isize) / 2;
+    (0..to_h as isize)
+        .flat_map(|y| (0..to_w as isize).map(move |x| (x - off_x, y - off_y)))
+        .map(|(x, y)| {
+            if (0..from_w as isize).contains(&x) && (0..from_h as isize).contains(&y) {
+                image[y as usize * from_w + x as usize]
+            } else {
+                TRANSPARENT
+            }
+        })
+        .collect()
+}
+
+/// Resamples a moose between the default and HD sizes, see [`ResampleQuery`].
+/// Moose that are already the requested size are returned as is.
+pub fn resample_moose(mut moose: Moose, opts: &ResampleQuery) -> Moose {
+    let Some(size) = opts.size else {
+        return moose;
+    };
+    let dimensions = Dimensions::from(size);
+    if moose.dimensions == dimensions {
+        return moose;
+    }
+    let (from_w, from_h, _) = moose.dimensions.width_height();
+    let (to_w, to_h, _) = dimensions.width_height();
+    moose.image = if opts.center {
+        centered(&moose.image, (from_w, from_h), (to_w, to_h))
+    } else {
+        nearest_neighbor(&moose.image, (from_w, from_h), (to_w, to_h))
+    };
+    moose.dimensions = dimensions;
+    moose
+}
+
+#[cfg(test)]
+mod tests {
+    use super::resample_moose;
+    use crate::model::{
+        color::TRANSPARENT,
+        dimensions::{DEFAULT_SIZE, Dimensions, HD_SIZE},
+        moose::Moose,
+        queries::{MooseSize, ResampleQuery},
+    };
+
+    fn resample(moose: Moose, size: MooseSize, center: bool) -> Moose {
+        resample_moose(
+            moose,
+            &ResampleQuery {
+                size: Some(size),
+                center,
+            },
+        )
+    }
+
+    #[test]
+    fn test_resample() {
+        let moose = Moose::for_test(
+            "gradient",
+            (0..DEFAULT_SIZE.2).map(|i| (i % 99) as u8).collect(),
+            Dimensions::Default,
+        );
+
+        let scaled = resample(moose.clone(), MooseSize::HD, false);
+        assert_eq!(scaled.dimensions.width_height(), HD_SIZE);
+        assert_eq!(scaled.image[0], moose.image[0]);
+        assert_eq!(scaled.image[HD_SIZE.2 - 1], moose.image[DEFAULT_SIZE.2 - 1]);
+
+        let centered = resample(moose.clone(), MooseSize::HD, true);
+        let (off_x, off_y) = (
+            (HD_SIZE.0 - DEFAULT_SIZE.0) / 2,
+            (HD_SIZE.1 - DEFAULT_SIZE.1) / 2,
+        );
+        assert_eq!(centered.image[off_y * HD_SIZE.0 + off_x], moose.image[0]);
+        assert_eq!(centered.image[off_y * HD_SIZE.0 + off_x - 1], TRANSPARENT);
+        // cropping back down undoes the padding.
+        let cropped = resample(centered, MooseSize::Default, true);
+        assert_eq!(cropped.image, moose.image);
+    }
+}
@@ -0,0 +1,200 @@
+/* Copyright (C) 2025  Anthony DeDominic
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::fmt::Write as _;
+
+use crate::{
+    model::{moose::Moose, queries::ImageQuery},
+    render::{
+        image::{Raster, rasterize},
+        textual::{TERM_BOLD, TERM_BOLD_END, format_info},
+    },
+};
+
+/// Device Control String: pixel aspect ratio 1:1 (0), leave transparent pixels alone (1).
+const SIXEL_START: &str = "\x1bP0;1;0q";
+const SIXEL_END: &str = "\x1b\\";
+/// a sixel is six vertical pixels.
+const SIXEL_HEIGHT: usize = 6;
+/// sixel data characters start at '?', which has no pixels set.
+const SIXEL_OFFSET: u8 = 0x3F;
+/// runs longer than this are cheaper to repeat with "!{count}{sixel}".
+const SIXEL_MIN_REPEAT: usize = 3;
+
+/// Sixel color registers use percentages, not 0..=255.
+fn percent(channel: u8) -> u32 {
+    (channel as u32 * 100 + 127) / 255
+}
+
+/// Encode a raster as sixels, one color register per palette entry.
+pub fn draw_sixel(raster: &Raster) -> String {
+    let width = raster.width;
+    let pixels = raster.pixels().collect::<Vec<u8>>();
+    let mut ret = String::with_capacity(4096);
+    write!(
+        &mut ret,
+        "{SIXEL_START}\"1;1;{width};{height}",
+        height = raster.height
+    )
+    .unwrap();
+    raster
+        .plte
+        .chunks_exact(3)
+        .enumerate()
+        .for_each(|(i, rgb)| {
+            let (r, g, b) = (percent(rgb[0]), percent(rgb[1]), percent(rgb[2]));
+            write!(&mut ret, "#{i};2;{r};{g};{b}").unwrap();
+        });
+    pixels.chunks(width * SIXEL_HEIGHT).for_each(|band| {
+        let rows = band.len() / width;
+        let mut used = [false; 256];
+        band.iter().for_each(|&pix| used[pix as usize] = true);
+        let colors = (0..used.len())
+            // transparent pixels are never drawn.
+            .filter(|&color| used[color] && !(raster.trns && color == 0))
+            .collect::<Vec<usize>>();
+        colors.iter().enumerate().for_each(|(i, &color)| {
+            if i > 0 {
+                // go back to the start of the band for the next color.
+                ret.push('$');
+            }
+            write!(&mut ret, "#{color}").unwrap();
+            let sixels = (0..width)
+                .map(|x| {
+                    SIXEL_OFFSET
+                        + (0..rows).fold(0, |bits, y| {
+                            bits | (u8::from(band[y * width + x] as usize == color) << y)
+                        })
+                })
+                .collect::<Vec<u8>>();
+            let trailing = sixels
+                .iter()
+                .rev()
+                .take_while(|&&sixel| sixel == SIXEL_OFFSET)
+                .count();
+            sixels[..sixels.len() - trailing]
+                .chunk_by(|a, b| a == b)
+                .for_each(|run| {
+                    if run.len() > SIXEL_MIN_REPEAT {
+                        write!(&mut ret, "!{}{}", run.len(), run[0] as char).unwrap();
+                    } else {
+                        run.iter().for_each(|&sixel| ret.push(sixel as char));
+                    }
+                });
+        });
+        ret.push('-');
+    });
+    ret.push_str(SIXEL_END);
+    ret
+}
+
+/// Given a moose and rendering options, returns a sixel rendering followed by the moose info.
+pub fn moose_sixel(moose: &Moose, opts: &ImageQuery) -> Vec<u8> {
+    let mut ret = draw_sixel(&rasterize(moose, opts));
+    ret.push('\n');
+    ret.push_str(&format_info(moose, TERM_BOLD, TERM_BOLD_END));
+    ret.into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SIXEL_END, SIXEL_OFFSET, SIXEL_START, draw_sixel};
+    use crate::{
+        model::{color::TRANSPARENT, dimensions::Dimensions, moose::Moose, queries::ImageQuery},
+        render::image::rasterize,
+    };
+
+    /// Paint sixel data onto a canvas, transparent pixels are None.
+    fn decode_sixel(sixel: &str, width: usize, height: usize) -> Vec<Option<usize>> {
+        let body = sixel
+            .strip_prefix(SIXEL_START)
+            .and_then(|s| s.strip_suffix(SIXEL_END))
+            .expect("sixel start and end.");
+        let mut canvas = vec![None; width * height];
+        let (mut x, mut band, mut color) = (0, 0, 0);
+        let mut chars = body.chars().peekable();
+        let number = |chars: &mut std::iter::Peekable<std::str::Chars>| {
+            let mut n = 0;
+            while let Some(d) = chars.next_if(char::is_ascii_digit) {
+                n = n * 10 + d.to_digit(10).unwrap() as usize;
+            }
+            n
+        };
+        while let Some(chr) = chars.next() {
+            let repeat = match chr {
+                '"' => {
+                    (0..4).for_each(|_| {
+                        number(&mut chars);
+                        chars.next_if_eq(&';');
+                    });
+                    continue;
+                }
+                '#' => {
+                    color = number(&mut chars);
+                    // skip color register definitions.
+                    while chars.next_if_eq(&';').is_some() {
+                        number(&mut chars);
+                    }
+                    continue;
+                }
+                '$' => {
+                    x = 0;
+                    continue;
+                }
+                '-' => {
+                    (x, band) = (0, band + 1);
+                    continue;
+                }
+                '!' => number(&mut chars),
+                _ => 1,
+            };
+            let sixel = if chr == '!' {
+                chars.next().unwrap()
+            } else {
+                chr
+            } as u8
+                - SIXEL_OFFSET;
+            for _ in 0..repeat {
+                (0..6)
+                    .filter(|y| sixel & (1 << y) != 0)
+                    .for_each(|y| canvas[(band * 6 + y) * width + x] = Some(color));
+                x += 1;
+            }
+        }
+        canvas
+    }
+
+    #[test]
+    fn test_sixel_matches_raster() {
+        let mut image = vec![TRANSPARENT; 26 * 15];
+        image[26 * 2 + 3..26 * 2 + 20].fill(4);
+        image[26 * 3 + 3..26 * 3 + 9].fill(52);
+        image[26 * 7 + 5] = 1;
+        let moose = Moose::for_test("test", image, Dimensions::Default);
+        let opts = ImageQuery {
+            width: Some(3),
+            height: Some(1),
+            ..Default::default()
+        };
+        let raster = rasterize(&moose, &opts);
+        let decoded = decode_sixel(&draw_sixel(&raster), raster.width, raster.height);
+        let expected = raster
+            .pixels()
+            .map(|pix| (!(raster.trns && pix == 0)).then_some(pix as usize))
+            .collect::<Vec<_>>();
+        assert_eq!(decoded, expected);
+    }
+}
@@ -0,0 +1,110 @@
+/* Copyright (C) 2025  Anthony DeDominic
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::collections::BTreeMap;
+
+use crate::{
+    model::{
+        PIX_FMT_HEIGHT, PIX_FMT_WIDTH, color::TRANSPARENT, moose::Moose, pages::SpriteRect,
+        queries::ImageQuery,
+    },
+    render::{
+        helpers::trim_moose,
+        image::{encode_png, rasterize_rows},
+    },
+};
+
+/// moose per sprite sheet row, a full page is a 4x3 grid.
+const SPRITE_COLUMNS: usize = 4;
+/// transparent moose pixels between sprites, so scaled sprites do not bleed into each other.
+const SPRITE_GAP: usize = 1;
+
+/// A trimmed moose and its position, measured in moose pixels.
+struct Sprite<'m> {
+    name: &'m str,
+    image: Vec<&'m [u8]>,
+    x: usize,
+    y: usize,
+}
+
+/// Trimmed meese placed on a shared canvas, measured in moose pixels.
+/// Laying out only borrows rows of each moose; the canvas is painted by png().
+pub struct SpriteSheet<'m> {
+    meese: Vec<Sprite<'m>>,
+    width: usize,
+    height: usize,
+}
+
+impl<'m> SpriteSheet<'m> {
+    /// Lay out meese in rows, left to right.
+    pub fn new(meese: &'m [Moose]) -> Self {
+        let mut placed = vec![];
+        let (mut width, mut height) = (0, 0);
+        meese.chunks(SPRITE_COLUMNS).for_each(|row| {
+            let mut x = 0;
+            let mut row_height = 0;
+            row.iter().for_each(|moose| {
+                let image = trim_moose(&moose.image, &moose.dimensions);
+                let (w, h) = (image[0].len(), image.len());
+                placed.push(Sprite {
+                    name: &moose.name,
+                    image,
+                    x,
+                    y: height,
+                });
+                x += w + SPRITE_GAP;
+                row_height = row_height.max(h);
+            });
+            width = width.max(x - SPRITE_GAP);
+            height += row_height + SPRITE_GAP;
+        });
+        SpriteSheet {
+            meese: placed,
+            width,
+            height: height.saturating_sub(SPRITE_GAP),
+        }
+    }
+
+    /// The name of every moose and where it is in the rendered sprite sheet.
+    pub fn rects(&self) -> BTreeMap<&'m str, SpriteRect> {
+        self.meese
+            .iter()
+            .map(|Sprite { name, image, x, y }| {
+                let rect = SpriteRect {
+                    x: x * PIX_FMT_WIDTH,
+                    y: y * PIX_FMT_HEIGHT,
+                    w: image[0].len() * PIX_FMT_WIDTH,
+                    h: image.len() * PIX_FMT_HEIGHT,
+                };
+                (*name, rect)
+            })
+            .collect()
+    }
+
+    /// Render the sprite sheet as a PNG; an empty sheet is a single transparent pixel.
+    pub fn png(&self) -> Vec<u8> {
+        let (width, height) = (self.width.max(1), self.height.max(1));
+        let mut canvas = vec![TRANSPARENT; width * height];
+        self.meese.iter().for_each(|Sprite { image, x, y, .. }| {
+            image.iter().enumerate().for_each(|(row_y, row)| {
+                let start = (y + row_y) * width + x;
+                canvas[start..start + row.len()].copy_from_slice(row);
+            })
+        });
+        let rows = canvas.chunks_exact(width).collect::<Vec<&[u8]>>();
+        encode_png(rasterize_rows(rows, &ImageQuery::default()))
+    }
+}
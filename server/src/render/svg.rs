@@ -0,0 +1,159 @@
+/* Copyright (C) 2025  Anthony DeDominic
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use std::fmt::Write as _;
+
+use crate::{
+    model::{
+        color::{EXTENDED_COLORS, RGBA, TRANSPARENT},
+        moose::Moose,
+        queries::ImageQuery,
+    },
+    render::image::{GRID_COLOR, moose_rows},
+};
+
+fn rect(svg: &mut String, x: usize, y: usize, width: usize, height: usize, color: u8) {
+    let RGBA(r, g, b, _) = EXTENDED_COLORS[color as usize];
+    write!(
+        svg,
+        r##"<rect x="{x}" y="{y}" width="{width}" height="{height}" fill="#{r:02x}{g:02x}{b:02x}"/>"##
+    )
+    .unwrap();
+}
+
+/// Given a moose and rendering options, returns an SVG rendering laid out like moose_png.
+/// Horizontal runs of the same color are merged into a single rect to keep the document small.
+pub fn moose_svg(moose: &Moose, opts: &ImageQuery) -> Vec<u8> {
+    let image = moose_rows(moose, opts);
+    let (dim_x, dim_y) = image
+        .first()
+        .map(|row| (row.len(), image.len()))
+        .expect("trim_moose always returns at least one pixel.");
+    let (cell_w, cell_h) = opts.cell_size();
+    let line = usize::from(opts.grid);
+    let (width, height) = (
+        (cell_w + line) * dim_x + line,
+        (cell_h + line) * dim_y + line,
+    );
+
+    let mut ret = String::with_capacity(4096);
+    write!(
+        &mut ret,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" shape-rendering="crispEdges">"#
+    )
+    .unwrap();
+    if opts.grid {
+        (0..=dim_x).for_each(|x| rect(&mut ret, x * (cell_w + 1), 0, 1, height, GRID_COLOR));
+        (0..=dim_y).for_each(|y| rect(&mut ret, 0, y * (cell_h + 1), width, 1, GRID_COLOR));
+    }
+    image.iter().enumerate().for_each(|(y, row)| {
+        let mut x = 0;
+        // runs would paint over the grid lines between their pixels.
+        for run in row.chunk_by(|a, b| !opts.grid && a == b) {
+            let pixel = match (run[0], opts.background) {
+                (TRANSPARENT, Some(bg)) => bg,
+                (pixel, _) => pixel,
+            };
+            if pixel != TRANSPARENT {
+                rect(
+                    &mut ret,
+                    x * (cell_w + line) + line,
+                    y * (cell_h + line) + line,
+                    run.len() * (cell_w + line) - line,
+                    cell_h,
+                    pixel,
+                );
+            }
+            x += run.len();
+        }
+    });
+    ret.push_str("</svg>");
+    ret.into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::moose_svg;
+    use crate::{
+        model::{color::TRANSPARENT, dimensions::Dimensions, moose::Moose, queries::ImageQuery},
+        render::image::rasterize,
+    };
+
+    /// Paint the rects of an SVG onto a canvas of RGB colors, nothing painted is None.
+    fn paint_svg(svg: &str) -> (usize, usize, Vec<Option<[u8; 3]>>) {
+        let attr = |tag: &str, name: &str| {
+            let start = tag.find(&format!(" {name}=\"")).unwrap() + name.len() + 3;
+            tag[start..].split('"').next().unwrap().to_owned()
+        };
+        let (head, body) = svg.split_once('>').unwrap();
+        let width: usize = attr(head, "width").parse().unwrap();
+        let height: usize = attr(head, "height").parse().unwrap();
+        let mut canvas = vec![None; width * height];
+        body.split("/>")
+            .filter(|tag| tag.starts_with("<rect"))
+            .for_each(|tag| {
+                let [x, y, w, h]: [usize; 4] =
+                    ["x", "y", "width", "height"].map(|name| attr(tag, name).parse().unwrap());
+                let fill = u32::from_str_radix(&attr(tag, "fill")[1..], 16).unwrap();
+                let [_, r, g, b] = fill.to_be_bytes();
+                (y..y + h)
+                    .for_each(|y| canvas[y * width + x..y * width + x + w].fill(Some([r, g, b])));
+            });
+        (width, height, canvas)
+    }
+
+    #[test]
+    fn test_svg_matches_raster() {
+        let mut image = vec![TRANSPARENT; Dimensions::Default.width_height().2];
+        image[26 * 2 + 3..26 * 2 + 20].fill(4);
+        image[26 * 3 + 3..26 * 3 + 9].fill(52);
+        image[26 * 7 + 5] = 1;
+        let moose = Moose::for_test("test", image, Dimensions::Default);
+        [
+            ImageQuery::default(),
+            ImageQuery {
+                width: Some(3),
+                height: Some(5),
+                grid: true,
+                ..Default::default()
+            },
+            ImageQuery {
+                width: Some(2),
+                square: true,
+                untrimmed: true,
+                background: Some(88),
+                ..Default::default()
+            },
+        ]
+        .iter()
+        .for_each(|opts| {
+            let svg = String::from_utf8(moose_svg(&moose, opts)).unwrap();
+            let (width, height, canvas) = paint_svg(&svg);
+            let raster = rasterize(&moose, opts);
+            assert_eq!((width, height), (raster.width, raster.height));
+            let expected = raster
+                .pixels()
+                .map(|pix| {
+                    (!raster.trns || pix != 0).then(|| {
+                        let i = pix as usize * 3;
+                        [raster.plte[i], raster.plte[i + 1], raster.plte[i + 2]]
+                    })
+                })
+                .collect::<Vec<_>>();
+            assert!(canvas == expected);
+        });
+    }
+}
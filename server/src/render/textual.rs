@@ -1,7 +1,11 @@
 use crate::{
     model::{
-        color::{EXTENDED_COLORS, RGBA, TRANSPARENT},
+        color::{
+            EXTENDED_COLORS, EXTENDED_TO_LEGACY, EXTENDED_TO_XTERM256, RGBA, TRANSPARENT,
+            color_distance,
+        },
         moose::Moose,
+        queries::{EmojiQuery, IrcColors, IrcQuery, TermColors, TermQuery, TextFormat},
     },
     render::helpers::trim_moose,
 };
@@ -21,8 +25,88 @@ fn single_pixel_irc(pixel: u8) -> Vec<u8> {
     }
 }
 
-const TERM_BOLD: &str = "\x1b[1m";
-const TERM_BOLD_END: &str = "\x1b[0m";
+/// Splits a row of pixels into IRC lines of at most max_len bytes.
+/// With a budget, trailing transparent pixels are dropped and lines never start with a stale color;
+/// without one, the row is a single line, as it always was.
+fn irc_row(row: &[u8], max_len: Option<usize>, lines: &mut Vec<Vec<u8>>) {
+    let trailing = match max_len {
+        Some(_) => row.iter().rev().take_while(|&&p| p == TRANSPARENT).count(),
+        None => 0,
+    };
+    let max_len = max_len.unwrap_or(usize::MAX);
+    let mut line = vec![];
+    let mut last_pix = TRANSPARENT;
+    for &pix in &row[..row.len() - trailing] {
+        let mut token = if pix == last_pix {
+            vec![pix_char_irc(pix)]
+        } else {
+            single_pixel_irc(pix)
+        };
+        if line.len() + token.len() > max_len {
+            lines.push(std::mem::take(&mut line));
+            // IRC lines start without color, so re-emit the active one.
+            token = if pix == TRANSPARENT {
+                vec![pix_char_irc(pix)]
+            } else {
+                single_pixel_irc(pix)
+            };
+        }
+        line.extend(token);
+        last_pix = pix;
+    }
+    // IRC does not allow empty messages.
+    if line.is_empty() {
+        line.push(b' ');
+    }
+    lines.push(line);
+}
+
+/// Given a moose and IRC options, returns every line of the mIRC colored rendering.
+fn moose_irc_lines(moose: &Moose, opts: &IrcQuery) -> Vec<String> {
+    let mut lines = vec![];
+    trim_moose(&moose.image, &moose.dimensions)
+        .into_iter()
+        .for_each(|row| match opts.colors {
+            IrcColors::Extended => irc_row(row, opts.max_len, &mut lines),
+            // recolor first, so neighboring pixels that now share a color also share a color code.
+            IrcColors::Legacy => {
+                let row = row
+                    .iter()
+                    .map(|&pix| EXTENDED_TO_LEGACY[pix as usize])
+                    .collect::<Vec<u8>>();
+                irc_row(&row, opts.max_len, &mut lines)
+            }
+        });
+    lines
+        .into_iter()
+        .map(|line| String::from_utf8(line).expect("IRC color codes are ASCII."))
+        .chain(
+            format_info(moose, IRC_BOLD, IRC_BOLD)
+                .lines()
+                .map(str::to_owned),
+        )
+        .chain(
+            opts.description
+                .then(|| moose.description.clone())
+                .flatten(),
+        )
+        .collect()
+}
+
+/// Given a moose and IRC options, returns an mIRC colored rendering, either as text or a JSON array of lines.
+pub fn moose_irc(moose: &Moose, opts: &IrcQuery) -> Vec<u8> {
+    let lines = moose_irc_lines(moose, opts);
+    match opts.format {
+        TextFormat::Text => lines
+            .into_iter()
+            .flat_map(|line| line.into_bytes().into_iter().chain(IRC_LINE_END.to_vec()))
+            .collect(),
+        TextFormat::Json => serde_json::to_vec(&lines).unwrap(),
+    }
+}
+
+pub const TERM_BOLD: &str = "\x1b[1m";
+pub const TERM_BOLD_END: &str = "\x1b[0m";
 const TERM_LINE_END: &[u8] = b"\x1b[0m\n";
 
 fn pix_char_term(_pixel: u8) -> u8 {
@@ -38,7 +122,169 @@ fn single_pixel_term(pixel: u8) -> Vec<u8> {
     }
 }
 
-fn format_info(moose: &Moose, bold_start: &'static str, bold_end: &'static str) -> String {
+fn single_pixel_term256(pixel: u8) -> Vec<u8> {
+    if pixel == TRANSPARENT {
+        b"\x1b[0m ".to_vec()
+    } else {
+        format!("\x1b[48;5;{}m ", EXTENDED_TO_XTERM256[pixel as usize]).into()
+    }
+}
+
+/// SGR parameters to set the foreground (38) or background (48) color.
+fn term_sgr(pixel: u8, layer: u8, colors: TermColors) -> String {
+    match colors {
+        TermColors::TrueColor => {
+            let RGBA(r, g, b, _) = EXTENDED_COLORS[pixel as usize];
+            format!("{layer};2;{r};{g};{b}")
+        }
+        TermColors::Xterm256 => format!("{layer};5;{}", EXTENDED_TO_XTERM256[pixel as usize]),
+    }
+}
+
+const TERM_FG: u8 = 38;
+const TERM_BG: u8 = 48;
+const UPPER_HALF: char = '\u{2580}';
+const LOWER_HALF: char = '\u{2584}';
+
+/// Packs two rows of pixels into every line using upper and lower half blocks.
+fn term_half_block(moose: &Moose, colors: TermColors) -> Vec<u8> {
+    let image = trim_moose(&moose.image, &moose.dimensions);
+    let blank = vec![TRANSPARENT; image[0].len()];
+    let mut ret = String::new();
+    image.chunks(2).for_each(|rows| {
+        let (top, bottom) = (rows[0], rows.get(1).copied().unwrap_or(&blank));
+        let mut last_pair = None;
+        for pair in top.iter().copied().zip(bottom.iter().copied()) {
+            let chr = match pair {
+                (TRANSPARENT, TRANSPARENT) => ' ',
+                (_, TRANSPARENT) => UPPER_HALF,
+                (TRANSPARENT, _) => LOWER_HALF,
+                _ => UPPER_HALF,
+            };
+            if last_pair != Some(pair) {
+                last_pair = Some(pair);
+                ret.push_str(&match pair {
+                    (TRANSPARENT, TRANSPARENT) => "\x1b[0m".to_owned(),
+                    (top, TRANSPARENT) => format!("\x1b[0;{}m", term_sgr(top, TERM_FG, colors)),
+                    (TRANSPARENT, bottom) => {
+                        format!("\x1b[0;{}m", term_sgr(bottom, TERM_FG, colors))
+                    }
+                    (top, bottom) => format!(
+                        "\x1b[{};{}m",
+                        term_sgr(top, TERM_FG, colors),
+                        term_sgr(bottom, TERM_BG, colors)
+                    ),
+                });
+            }
+            ret.push(chr);
+        }
+        ret.push_str(str::from_utf8(TERM_LINE_END).unwrap());
+    });
+    ret.push_str(&format_info(moose, TERM_BOLD, TERM_BOLD_END));
+    ret.into_bytes()
+}
+
+/// Given a moose and terminal options, returns an ANSI colored rendering.
+pub fn moose_term(moose: &Moose, opts: &TermQuery) -> Vec<u8> {
+    let mut ret = match (opts.half, opts.colors) {
+        (true, colors) => term_half_block(moose, colors),
+        (false, TermColors::TrueColor) => term_truecolor(moose),
+        (false, TermColors::Xterm256) => term_256(moose),
+    };
+    if let (true, Some(description)) = (opts.description, &moose.description) {
+        ret.extend(description.as_bytes());
+        ret.push(b'\n');
+    }
+    ret
+}
+
+const EMOJI_BOLD: &str = "**";
+/// Colored square emoji and roughly how they look on most platforms.
+const EMOJI_SQUARES: [(RGBA, &str); 9] = [
+    (RGBA(0xdd, 0x2e, 0x44, 0xff), "\u{1f7e5}"), // red
+    (RGBA(0xf4, 0x90, 0x0c, 0xff), "\u{1f7e7}"), // orange
+    (RGBA(0xfd, 0xcb, 0x58, 0xff), "\u{1f7e8}"), // yellow
+    (RGBA(0x78, 0xb1, 0x59, 0xff), "\u{1f7e9}"), // green
+    (RGBA(0x55, 0xac, 0xee, 0xff), "\u{1f7e6}"), // blue
+    (RGBA(0xaa, 0x8e, 0xd6, 0xff), "\u{1f7ea}"), // purple
+    (RGBA(0xc1, 0x69, 0x4f, 0xff), "\u{1f7eb}"), // brown
+    (RGBA(0x31, 0x37, 0x3d, 0xff), "\u{2b1b}"),  // black
+    (RGBA(0xe6, 0xe7, 0xe8, 0xff), "\u{2b1c}"),  // white
+];
+
+const EMOJI_BLACK: usize = 7;
+const EMOJI_WHITE: usize = 8;
+
+/// Perceived brightness of a color, 0..=255.
+fn luma(RGBA(r, g, b, _): RGBA) -> u32 {
+    (r as u32 * 299 + g as u32 * 587 + b as u32 * 114) / 1000
+}
+
+fn nearest_emoji(pixel: u8) -> &'static str {
+    let color @ RGBA(r, g, b, _) = EXTENDED_COLORS[pixel as usize];
+    // there are no gray squares and grays tend to land on green otherwise, so split them by brightness.
+    if r.max(g).max(b) - r.min(g).min(b) < 0x20 {
+        return EMOJI_SQUARES[if luma(color) < 0x80 {
+            EMOJI_BLACK
+        } else {
+            EMOJI_WHITE
+        }]
+        .1;
+    }
+    EMOJI_SQUARES
+        .iter()
+        .min_by_key(|(square, _)| color_distance(color, *square))
+        .map(|(_, emoji)| *emoji)
+        .expect("EMOJI_SQUARES is not empty.")
+}
+
+/// Given a moose and emoji options, returns a grid of colored square emoji.
+pub fn moose_emoji(moose: &Moose, opts: &EmojiQuery) -> Vec<u8> {
+    let mut ret = String::new();
+    trim_moose(&moose.image, &moose.dimensions)
+        .into_iter()
+        .for_each(|row| {
+            row.iter().for_each(|&pix| {
+                ret.push_str(if pix == TRANSPARENT {
+                    opts.blank()
+                } else {
+                    nearest_emoji(pix)
+                })
+            });
+            ret.push('\n');
+        });
+    ret.push_str(&format_info(moose, EMOJI_BOLD, EMOJI_BOLD));
+    ret.into_bytes()
+}
+
+/// Lightest to densest; the space is reserved for transparency.
+const ASCII_RAMP: &[u8] = b" .:-=+*#%@";
+
+/// Darker pixels get denser characters, as if printed on a light page.
+fn ascii_density(pixel: u8) -> u8 {
+    if pixel == TRANSPARENT {
+        return ASCII_RAMP[0];
+    }
+    let darkness = 0xff - luma(EXTENDED_COLORS[pixel as usize]) as usize;
+    ASCII_RAMP[1 + darkness * (ASCII_RAMP.len() - 2) / 0xff]
+}
+
+/// Given a moose, returns a grayscale rendering with plain ASCII characters, no control codes.
+pub fn moose_ascii(moose: &Moose) -> Vec<u8> {
+    let mut ret = String::new();
+    trim_moose(&moose.image, &moose.dimensions)
+        .into_iter()
+        .for_each(|row| {
+            let line = row.iter().map(|&pix| ascii_density(pix) as char);
+            ret.extend(line);
+            ret.truncate(ret.trim_end_matches(' ').len());
+            ret.push('\n');
+        });
+    ret.push_str(&format_info(moose, "", ""));
+    ret.into_bytes()
+}
+
+pub fn format_info(moose: &Moose, bold_start: &'static str, bold_end: &'static str) -> String {
     use std::fmt::Write as _;
     let mut ret = String::new();
     write!(&mut ret, "{bold_start}{}{bold_end}", moose.name).unwrap();
@@ -49,6 +295,10 @@ fn format_info(moose: &Moose, bold_start: &'static str, bold_end: &'static str)
         write!(&mut ret, " \u{2bc5}{}", moose.upvotes).unwrap();
     }
     writeln!(&mut ret, " created {}", moose.created.date()).unwrap();
+    // on a line of its own, so IRC lines still fit two full moose names.
+    if let Some(parent) = &moose.parent {
+        writeln!(&mut ret, "remix of {bold_start}{parent}{bold_end}").unwrap();
+    }
     ret
 }
 
@@ -77,18 +327,208 @@ macro_rules! impl_line {
 }
 
 impl_line!(
-    moose_irc,
-    pix_char_irc,
-    single_pixel_irc,
-    IRC_LINE_END,
-    IRC_BOLD,
-    IRC_BOLD
+    term_truecolor,
+    pix_char_term,
+    single_pixel_term,
+    TERM_LINE_END,
+    TERM_BOLD,
+    TERM_BOLD_END
 );
 impl_line!(
-    moose_term,
+    term_256,
     pix_char_term,
-    single_pixel_term,
+    single_pixel_term256,
     TERM_LINE_END,
     TERM_BOLD,
     TERM_BOLD_END
 );
+
+#[cfg(test)]
+mod tests {
+    use axum::extract::Query;
+
+    use super::{
+        ASCII_RAMP, ascii_density, luma, moose_ascii, moose_emoji, moose_irc, moose_irc_lines,
+        moose_term,
+    };
+    use crate::model::{
+        color::{EXTENDED_COLORS, EXTENDED_TO_XTERM256, TRANSPARENT},
+        dimensions::{Dimensions, HD_SIZE},
+        moose::Moose,
+        queries::{EmojiQuery, IRC_MIN_LINE_LEN, IrcQuery, TermColors, TermQuery},
+    };
+
+    /// Strip color codes, leaving the pixel characters behind.
+    fn strip_colors(line: &str) -> String {
+        let mut ret = String::new();
+        let mut chars = line.chars().peekable();
+        while let Some(chr) = chars.next() {
+            if chr == '\x03' {
+                while chars.next_if(|c| c.is_ascii_digit() || *c == ',').is_some() {}
+            } else {
+                ret.push(chr);
+            }
+        }
+        ret
+    }
+
+    #[test]
+    fn test_irc_line_budget() {
+        // every pixel changes color, the worst case for line length.
+        let image = (0..HD_SIZE.2)
+            .map(|i| {
+                if i % 36 == 35 {
+                    TRANSPARENT
+                } else {
+                    (i % 99) as u8
+                }
+            })
+            .collect();
+        let moose = Moose::for_test("test", image, Dimensions::HD);
+        let unbounded = moose_irc_lines(&moose, &IrcQuery::default());
+        let bounded = moose_irc_lines(
+            &moose,
+            &IrcQuery {
+                max_len: Some(IRC_MIN_LINE_LEN),
+                ..Default::default()
+            },
+        );
+        assert!(unbounded.iter().any(|line| line.len() > IRC_MIN_LINE_LEN));
+        assert!(bounded.iter().all(|line| line.len() <= IRC_MIN_LINE_LEN));
+        assert!(bounded.len() > unbounded.len());
+        // continuation lines re-emit their color, the last line is the moose info.
+        assert!(
+            bounded[..bounded.len() - 1]
+                .iter()
+                .all(|line| line.starts_with('\x03'))
+        );
+        // the description is opt-in and gets a line of its own.
+        let described = moose_irc_lines(
+            &Moose {
+                description: Some("a test moose".to_owned()),
+                ..moose.clone()
+            },
+            &IrcQuery {
+                description: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(described.len(), unbounded.len() + 1);
+        assert_eq!(described.last().unwrap(), "a test moose");
+        let remix = moose_irc_lines(
+            &Moose {
+                parent: Some("parent".to_owned()),
+                ..moose.clone()
+            },
+            &IrcQuery::default(),
+        );
+        assert_eq!(remix.len(), unbounded.len() + 1);
+        assert_eq!(remix.last().unwrap(), "remix of \x02parent\x02");
+        // trailing transparency is dropped once there is a budget.
+        assert!(bounded.iter().all(|line| !line.ends_with("\x03 ")));
+        assert_eq!(
+            unbounded
+                .iter()
+                .map(|l| strip_colors(l).trim_end().to_owned())
+                .collect::<String>(),
+            bounded.iter().map(|l| strip_colors(l)).collect::<String>(),
+        );
+    }
+
+    #[test]
+    fn test_irc_default_unchanged() {
+        let moose = Moose::for_test(
+            "test",
+            vec![4, 4, TRANSPARENT, TRANSPARENT, 52, 1],
+            Dimensions::Custom(3, 2),
+        );
+        assert_eq!(
+            moose_irc(&moose, &IrcQuery::default()),
+            b"\x034,4@@\x03 \n \x0352,52@\x031,1@\n\x02test\x02 created 1970-01-01\n"
+        );
+    }
+
+    #[test]
+    fn test_ascii_density() {
+        // brighter colors never get denser characters, and only transparency is blank.
+        let mut codes = (0..TRANSPARENT).collect::<Vec<u8>>();
+        codes.sort_by_key(|&code| std::cmp::Reverse(luma(EXTENDED_COLORS[code as usize])));
+        let ramp = |pix| ASCII_RAMP.iter().position(|&chr| chr == ascii_density(pix));
+        assert!(codes.windows(2).all(|w| ramp(w[0]) <= ramp(w[1])));
+        assert_eq!(ramp(TRANSPARENT), Some(0));
+        assert!(codes.iter().all(|&code| ramp(code) > Some(0)));
+
+        // white, grey, black, then a transparent gap; trailing transparency is trimmed.
+        let moose = Moose::for_test(
+            "test",
+            vec![0, 14, 1, 1, TRANSPARENT, TRANSPARENT],
+            Dimensions::Custom(3, 2),
+        );
+        assert_eq!(
+            String::from_utf8(moose_ascii(&moose)).unwrap(),
+            ".=@\n@\ntest created 1970-01-01\n"
+        );
+    }
+
+    #[test]
+    fn test_emoji_squares() {
+        // red, black and white, then yellow and lime around a transparent pixel.
+        let moose = Moose::for_test(
+            "test",
+            vec![4, 1, 0, 8, TRANSPARENT, 9],
+            Dimensions::Custom(3, 2),
+        );
+        assert_eq!(
+            String::from_utf8(moose_emoji(&moose, &EmojiQuery::default())).unwrap(),
+            "\u{1f7e5}\u{2b1b}\u{2b1c}\n\u{1f7e8}\u{3000}\u{1f7e9}\n**test** created 1970-01-01\n"
+        );
+        let Query(dots) =
+            Query::<EmojiQuery>::try_from_uri(&"/?blank=..".parse().unwrap()).unwrap();
+        let dotted = String::from_utf8(moose_emoji(&moose, &dots)).unwrap();
+        assert_eq!(dotted.lines().nth(1), Some("\u{1f7e8}..\u{1f7e9}"));
+    }
+
+    #[test]
+    fn test_term_half_block_256() {
+        // three rows, so the last line pairs a row with blank pixels.
+        let moose = Moose::for_test(
+            "test",
+            vec![4, TRANSPARENT, 52, 4, 1, TRANSPARENT],
+            Dimensions::Custom(2, 3),
+        );
+        let x = |pix: usize| EXTENDED_TO_XTERM256[pix];
+        let half = moose_term(
+            &moose,
+            &TermQuery {
+                half: true,
+                colors: TermColors::Xterm256,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            String::from_utf8(half).unwrap(),
+            format!(
+                "\x1b[38;5;{};48;5;{}m\u{2580}\x1b[0;38;5;{}m\u{2584}\x1b[0m\n\
+                 \x1b[0;38;5;{}m\u{2580}\x1b[0m \x1b[0m\n\
+                 \x1b[1mtest\x1b[0m created 1970-01-01\n",
+                x(4),
+                x(52),
+                x(4),
+                x(1),
+            )
+        );
+        let full = moose_term(
+            &moose,
+            &TermQuery {
+                colors: TermColors::Xterm256,
+                ..Default::default()
+            },
+        );
+        let full = String::from_utf8(full).unwrap();
+        assert_eq!(
+            full.lines().next().unwrap(),
+            format!("\x1b[48;5;{}m \x1b[0m \x1b[0m", x(4))
+        );
+        assert_eq!(full.lines().count(), 4);
+    }
+}
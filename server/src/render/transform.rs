@@ -0,0 +1,137 @@
+/* Copyright (C) 2025  Anthony DeDominic
+ *
+ * This program is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::model::{
+    color::EXTENDED_INVERTED,
+    dimensions::Dimensions,
+    moose::Moose,
+    queries::{Rotation, TransformQuery},
+};
+
+/// Rotates the image clockwise by 90 degrees, or counter-clockwise if ccw is set.
+/// The result is height pixels wide and width pixels tall.
+fn rotate_quarter(image: &[u8], width: usize, height: usize, ccw: bool) -> Vec<u8> {
+    (0..width)
+        .flat_map(|y| (0..height).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            if ccw {
+                image[x * width + (width - 1 - y)]
+            } else {
+                image[(height - 1 - x) * width + y]
+            }
+        })
+        .collect()
+}
+
+/// Mirrors, flips, rotates and recolors a moose before it is handed to a renderer.
+/// Quarter turns swap the width and height, so the moose gets custom dimensions.
+pub fn transform_moose(mut moose: Moose, opts: &TransformQuery) -> Moose {
+    let (width, height, _) = moose.dimensions.width_height();
+    if opts.mirror {
+        moose
+            .image
+            .chunks_exact_mut(width)
+            .for_each(|row| row.reverse());
+    }
+    if opts.flip {
+        moose.image = moose
+            .image
+            .chunks_exact(width)
+            .rev()
+            .flatten()
+            .copied()
+            .collect();
+    }
+    match opts.rotate {
+        Rotation::None => (),
+        Rotation::Half => moose.image.reverse(),
+        rotate @ (Rotation::Quarter | Rotation::ThreeQuarter) => {
+            let ccw = matches!(rotate, Rotation::ThreeQuarter);
+            moose.image = rotate_quarter(&moose.image, width, height, ccw);
+            moose.dimensions = Dimensions::Custom(height, width);
+        }
+    }
+    if opts.invert {
+        moose
+            .image
+            .iter_mut()
+            .for_each(|pix| *pix = EXTENDED_INVERTED[*pix as usize]);
+    }
+    moose
+}
+
+#[cfg(test)]
+mod tests {
+    use super::transform_moose;
+    use crate::model::{
+        dimensions::{DEFAULT_SIZE, Dimensions},
+        moose::Moose,
+        queries::{Rotation, TransformQuery},
+    };
+
+    fn moose() -> Moose {
+        Moose::for_test(
+            "transform",
+            (0..DEFAULT_SIZE.2).map(|i| (i % 99) as u8).collect(),
+            Dimensions::Default,
+        )
+    }
+
+    #[test]
+    fn test_rotate() {
+        let (width, height, _) = DEFAULT_SIZE;
+        let quarter = transform_moose(
+            moose(),
+            &TransformQuery {
+                rotate: Rotation::Quarter,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            quarter.dimensions.width_height(),
+            (height, width, DEFAULT_SIZE.2)
+        );
+        // the bottom left corner becomes the top left corner.
+        assert_eq!(quarter.image[0], moose().image[(height - 1) * width]);
+
+        let back = transform_moose(
+            quarter,
+            &TransformQuery {
+                rotate: Rotation::ThreeQuarter,
+                ..Default::default()
+            },
+        );
+        assert_eq!(back.dimensions.width_height(), DEFAULT_SIZE);
+        assert_eq!(back.image, moose().image);
+
+        let half = transform_moose(
+            moose(),
+            &TransformQuery {
+                rotate: Rotation::Half,
+                ..Default::default()
+            },
+        );
+        let mirror_flip = transform_moose(
+            moose(),
+            &TransformQuery {
+                mirror: true,
+                flip: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(half.image, mirror_flip.image);
+    }
+}
@@ -130,5 +130,7 @@ pub const EXAMPLE_CONFIG: &[u8] = br###"{ "//": "OPTIONAL: default: $XDG_DATA_HO
     , "//": "How many buckets for IP rate limiting. multiply by 8 for the size in memory. default: 128KiB"
     , "bucket_size": 16384
     }
+, "//": "OPTIONAL: GitHub users that can delete any moose, not just their own."
+, "admins": []
 }
 "###;
@@ -83,6 +83,7 @@ pub fn web_task(
         db,
         cookie_key: Key::from(&rc.cookie_key.0),
         oauth2_client,
+        admins: rc.admins.clone(),
     });
     let moose_dump = rc.get_moose_dump();
 
@@ -21,17 +21,28 @@ use crate::{
         MooseDB,
         sqlite3_impl::{Pool, Sqlite3Error},
     },
+    import::{import_ansi, import_irc, import_png, new_moose},
     middleware::{etag::etag, ratelim::BucketRatelim},
     model::{
-        PAGE_SIZE,
+        PAGE_SIZE, PIX_FMT_MAX,
         author::{AuthenticatedAuthor, Author},
-        dimensions::Dimensions,
-        moose::Moose,
+        color::TRANSPARENT,
+        dimensions::{Dimensions, HD_SIZE},
+        draw::{DrawBase, DrawRequest},
+        moose::{Moose, MooseGrid, validate_name, validate_tags},
         pages::MooseSearchPage,
-        queries::SearchQuery,
+        queries::{
+            ArtFormat, BannerQuery, COMPOSE_MAX_MOOSE, COMPOSE_MIN_MOOSE, ComposeQuery, ImageQuery,
+            ImportQuery, IrcQuery, MooseFormat, MooseQuery, MooseSize, ResampleQuery, SearchQuery,
+            TagsQuery, TermQuery, TextFormat, TextImportQuery, TransformQuery,
+        },
         votes::VoteFlag,
     },
-    render::{moose_irc, moose_png, moose_term},
+    render::{
+        SpriteSheet, compose_moose, draw_moose, moose_ascii, moose_banner, moose_card, moose_emoji,
+        moose_gif, moose_irc, moose_png, moose_sixel, moose_svg, moose_term, resample_moose,
+        transform_moose,
+    },
     task::notify_new,
     templates,
     web_handlers::JSON_TYPE,
@@ -39,9 +50,13 @@ use crate::{
 use ::time::OffsetDateTime;
 use axum::{
     Json, Router,
-    extract::{Path, Query, State, rejection::JsonRejection},
+    body::Bytes,
+    extract::{
+        DefaultBodyLimit, Path, Query, State,
+        rejection::{JsonRejection, QueryRejection},
+    },
     response::{IntoResponse, Response},
-    routing::{get, put},
+    routing::{MethodRouter, get, post, put},
 };
 use core::time;
 use http::{
@@ -49,6 +64,7 @@ use http::{
     header::{CACHE_CONTROL, CONTENT_TYPE, ETAG, LOCATION},
 };
 use percent_encoding::{NON_ALPHANUMERIC, percent_encode};
+use serde::{Serialize, de::DeserializeOwned};
 use std::time::Duration;
 
 pub enum HeadType {
@@ -147,6 +163,68 @@ async fn resolve_moose(State(db): State<MooseWebData>, Path(moose_name): Path<St
     }
 }
 
+/// Each output format has its own rendering options, parsed from the query string.
+fn render_opts<T: DeserializeOwned>(uri: &Uri) -> Result<T, ApiError> {
+    Query::try_from_uri(uri)
+        .map(|Query(opts)| opts)
+        .map_err(|e| ApiError::new_with_status(StatusCode::BAD_REQUEST, e.body_text()))
+}
+
+fn render_moose(path: &str, moose: Moose, uri: &Uri) -> Result<(Vec<u8>, &'static str), ApiError> {
+    // the stored moose is served as is; every rendering can be resampled and transformed.
+    // both are part of the query string, so cached responses vary by them like any other option.
+    let moose = if path == "moose" {
+        moose
+    } else {
+        let moose = resample_moose(moose, &render_opts::<ResampleQuery>(uri)?);
+        transform_moose(moose, &render_opts::<TransformQuery>(uri)?)
+    };
+    Ok(match path {
+        "moose" => {
+            let opts: MooseQuery = render_opts(uri)?;
+            let json = match opts.format {
+                MooseFormat::Base64 => moose.into(),
+                MooseFormat::Grid => serde_json::to_vec(&MooseGrid::new(&moose, opts.palette))
+                    .expect("MooseGrid to serialize."),
+            };
+            (json, "application/json")
+        }
+        "img" => (moose_png(&moose, &render_opts(uri)?), "image/png"),
+        "gif" => (
+            moose_gif(&moose, &render_opts(uri)?)
+                .map_err(|e| ApiError::new_with_status(StatusCode::BAD_REQUEST, e))?,
+            "image/gif",
+        ),
+        "svg" => (moose_svg(&moose, &render_opts(uri)?), "image/svg+xml"),
+        "card" => (moose_card(&moose), "image/png"),
+        "irc" => {
+            let opts: IrcQuery = render_opts(uri)?;
+            let ctype = match opts.format {
+                TextFormat::Text => "text/irc-art",
+                TextFormat::Json => "application/json",
+            };
+            (moose_irc(&moose, &opts), ctype)
+        }
+        "sixel" => (moose_sixel(&moose, &render_opts(uri)?), "text/sixel"),
+        "ascii" => (moose_ascii(&moose), "text/plain; charset=utf-8"),
+        "emoji" => (
+            moose_emoji(&moose, &render_opts(uri)?),
+            "text/plain; charset=utf-8",
+        ),
+        "term" => {
+            let opts: TermQuery = render_opts(uri)?;
+            (moose_term(&moose, &opts), opts.colors.content_type())
+        }
+        // routes like /banner/{format} pass any format through.
+        _ => {
+            return Err(ApiError::new_with_status(
+                StatusCode::NOT_FOUND,
+                format!("no such format: {path}"),
+            ));
+        }
+    })
+}
+
 async fn get_moose(
     State(db): State<MooseWebData>,
     Path(moose_name): Path<String>,
@@ -160,23 +238,182 @@ async fn get_moose(
         ));
     };
     match simple_get(db, &moose_name).await {
-        Ok(Some(moose)) => {
-            let (body, ctype) = match path {
-                "moose" => (moose.into(), "application/json"),
-                "img" => (moose_png(&moose), "image/png"),
-                "irc" => (moose_irc(&moose), "text/irc-art"),
-                "term" => (moose_term(&moose), "text/ansi-truecolor"),
-                _ => {
-                    log::error!("Router is passing paths that don't make sense: {path:?}",);
-                    return ApiResp::CustomError(ApiError::new(format!(
-                        "Cannot fetch moose type: {path:?}"
-                    )));
-                }
-            };
-            ApiResp::Body(body, ctype)
-        }
+        Ok(Some(moose)) => match render_moose(path, moose, &uri) {
+            Ok((body, ctype)) => ApiResp::Body(body, ctype),
+            Err(e) => ApiResp::CustomError(e),
+        },
         Ok(None) => ApiResp::NotFound(moose_name.to_string()),
-        Err(redir) => ApiResp::Redirect(redir),
+        // keep rendering options so the redirected moose is rendered the same way.
+        Err(redir) => ApiResp::Redirect(match uri.query() {
+            Some(query) => format!("{redir}?{query}"),
+            None => redir,
+        }),
+    }
+}
+
+/// Largest composition we are willing to render; the same as an HD moose at the biggest pixel size.
+const COMPOSE_MAX_PIXELS: usize = HD_SIZE.2 * PIX_FMT_MAX * PIX_FMT_MAX;
+
+async fn compose_from_query(db: &Pool, uri: &Uri) -> Result<Moose, ApiResp> {
+    let names = Query::<Vec<(String, String)>>::try_from_uri(uri)
+        .map(|Query(params)| {
+            params
+                .into_iter()
+                .filter(|(key, _)| key == "moose")
+                .map(|(_, name)| name)
+                .collect::<Vec<String>>()
+        })
+        .map_err(|e| {
+            ApiResp::CustomError(ApiError::new_with_status(
+                StatusCode::BAD_REQUEST,
+                e.body_text(),
+            ))
+        })?;
+    if !(COMPOSE_MIN_MOOSE..=COMPOSE_MAX_MOOSE).contains(&names.len()) {
+        return Err(ApiResp::CustomError(ApiError::new_with_status(
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Compose between {COMPOSE_MIN_MOOSE} and {COMPOSE_MAX_MOOSE} moose, e.g. ?moose=a&moose=b"
+            ),
+        )));
+    }
+    let opts: ComposeQuery = render_opts(uri).map_err(ApiResp::CustomError)?;
+
+    let mut meese = Vec::with_capacity(names.len());
+    for name in names {
+        match db.get_moose(&name).await {
+            Ok(Some(moose)) => meese.push(moose),
+            Ok(None) => return Err(ApiResp::NotFound(name)),
+            Err(e) => {
+                log::error!("DB is broken (trying to get moose {name}): {e}");
+                return Err(ApiResp::CustomError(ApiError::new(e)));
+            }
+        }
+    }
+    let moose = compose_moose(&meese, &opts);
+
+    let (width, height, _) = moose.dimensions.width_height();
+    let (cell_w, cell_h) = render_opts::<ImageQuery>(uri)
+        .map_err(ApiResp::CustomError)?
+        .cell_size();
+    if width * cell_w * height * cell_h > COMPOSE_MAX_PIXELS {
+        return Err(ApiResp::CustomError(ApiError::new_with_status(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "Composition is too large to render; use fewer moose or smaller pixels.",
+        )));
+    }
+    Ok(moose)
+}
+
+async fn get_composed_moose(
+    State(db): State<MooseWebData>,
+    Path(format): Path<String>,
+    uri: Uri,
+) -> ApiResp {
+    let moose = match compose_from_query(&db.db, &uri).await {
+        Ok(moose) => moose,
+        Err(resp) => return resp,
+    };
+    match render_moose(&format, moose, &uri) {
+        Ok((body, ctype)) => ApiResp::Body(body, ctype),
+        Err(e) => ApiResp::CustomError(e),
+    }
+}
+
+/// Previews a text banner in any format, e.g. /banner/img?text=hello&fg=88
+async fn get_banner(Path(format): Path<String>, uri: Uri) -> ApiResp {
+    let opts: BannerQuery = match render_opts(&uri) {
+        Ok(opts) => opts,
+        Err(e) => return ApiResp::CustomError(e),
+    };
+    let image = match moose_banner(&opts) {
+        Ok(image) => image,
+        Err(e) => {
+            return ApiResp::CustomError(ApiError::new_with_status(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                e,
+            ));
+        }
+    };
+    let moose = Moose {
+        name: opts.text,
+        dimensions: Dimensions::from_len(&image).expect("banners to be default or HD sized."),
+        image,
+        created: OffsetDateTime::now_utc(),
+        author: Author::Anonymous,
+        upvotes: 0,
+        description: None,
+        parent: None,
+    };
+    match render_moose(&format, moose, &uri) {
+        Ok((body, ctype)) => ApiResp::Body(body, ctype),
+        Err(e) => ApiResp::CustomError(e),
+    }
+}
+
+/// Draws over a blank or existing moose, see [`DrawRequest`].
+async fn draw_from_request(db: &Pool, req: DrawRequest) -> Result<Moose, ApiError> {
+    let (name, image, dimensions, parent) = match req.base {
+        DrawBase::Blank(size) => {
+            let dimensions = Dimensions::from(size);
+            let image = vec![TRANSPARENT; dimensions.width_height().2];
+            ("drawing".to_owned(), image, dimensions, None)
+        }
+        // drawing over a moose makes a remix of it.
+        DrawBase::Moose(name) => match db.get_moose(&name).await {
+            Ok(Some(moose)) => (
+                moose.name.clone(),
+                moose.image,
+                moose.dimensions,
+                Some(moose.name),
+            ),
+            Ok(None) => {
+                return Err(ApiError::new_with_status(
+                    StatusCode::NOT_FOUND,
+                    format!("no such moose: {name}"),
+                ));
+            }
+            Err(e) => {
+                log::error!("DB is broken (trying to get moose {name}): {e}");
+                return Err(ApiError::new(e));
+            }
+        },
+    };
+    let (image, dimensions) = draw_moose(image, dimensions, &req.ops)
+        .map_err(|e| ApiError::new_with_status(StatusCode::UNPROCESSABLE_ENTITY, e))?;
+    Ok(Moose {
+        name: req.name.unwrap_or(name),
+        image,
+        dimensions,
+        created: OffsetDateTime::now_utc(),
+        author: Author::Anonymous,
+        upvotes: 0,
+        description: req.description,
+        parent,
+    })
+}
+
+/// Previews a drawing in any format, e.g. POST /draw/irc
+async fn post_draw(
+    State(db): State<MooseWebData>,
+    Path(format): Path<String>,
+    uri: Uri,
+    payload: Result<Json<DrawRequest>, JsonRejection>,
+) -> ApiResp {
+    let Json(req) = match payload {
+        Ok(req) => req,
+        Err(e) => {
+            return ApiResp::CustomError(ApiError::new_with_status(StatusCode::BAD_REQUEST, e));
+        }
+    };
+    let moose = match draw_from_request(&db.db, req).await {
+        Ok(moose) => moose,
+        Err(e) => return ApiResp::CustomError(e),
+    };
+    match render_moose(&format, moose, &uri) {
+        // drawings are made up on the spot, there is nothing to cache.
+        Ok((body, ctype)) => ApiResp::BodyCacheTime(body, ctype, Duration::from_secs(0)),
+        Err(e) => ApiResp::CustomError(e),
     }
 }
 
@@ -208,16 +445,52 @@ async fn get_page(
             log::error!("{err}");
             vec![]
         });
-    // if the page is full, it probably won't change in hours, if ever.
-    // if the page isn't full, it's the last page or a page we haven't gotten to yet and can change.
-    let cache_duration = if meese.len() < PAGE_SIZE {
+    let cache_duration = page_cache_duration(meese.len());
+    let meese = serde_json::to_vec(&meese).unwrap();
+    ApiResp::BodyCacheTime(meese, "application/json", cache_duration)
+}
+
+/// if the page is full, it probably won't change in hours, if ever.
+/// if the page isn't full, it's the last page or a page we haven't gotten to yet and can change.
+fn page_cache_duration(moose_count: usize) -> Duration {
+    if moose_count < PAGE_SIZE {
         Duration::from_secs(0) // last page or non-existent page.
     } else {
         Duration::from_secs(3600) // full page
+    }
+}
+
+async fn get_page_meese(db: &Pool, page_num: usize) -> Result<Vec<Moose>, ApiResp> {
+    // an empty sheet would be cached like a real one, so a broken DB has to be an error.
+    match db.get_moose_page(page_num, None).await {
+        Ok(page) => Ok(page.into_iter().map(|search| search.moose).collect()),
+        Err(e) => {
+            log::error!("DB is broken (trying to get page {page_num}): {e}");
+            Err(ApiResp::CustomError(ApiError::new(e)))
+        }
+    }
+}
+
+async fn get_page_sprite(State(db): State<MooseWebData>, Path(page_num): Path<usize>) -> ApiResp {
+    let meese = match get_page_meese(&db.db, page_num).await {
+        Ok(meese) => meese,
+        Err(e) => return e,
     };
+    let sheet = SpriteSheet::new(&meese).png();
+    ApiResp::BodyCacheTime(sheet, "image/png", page_cache_duration(meese.len()))
+}
 
-    let meese = serde_json::to_vec(&meese).unwrap();
-    ApiResp::BodyCacheTime(meese, "application/json", cache_duration)
+async fn get_page_sprite_map(
+    State(db): State<MooseWebData>,
+    Path(page_num): Path<usize>,
+) -> ApiResp {
+    let meese = match get_page_meese(&db.db, page_num).await {
+        Ok(meese) => meese,
+        Err(e) => return e,
+    };
+    // only the layout is needed, nothing is painted or encoded.
+    let rects = serde_json::to_vec(&SpriteSheet::new(&meese).rects()).unwrap();
+    ApiResp::BodyCacheTime(rects, "application/json", page_cache_duration(meese.len()))
 }
 
 async fn get_page_nav_range(
@@ -254,6 +527,8 @@ async fn get_search_page(
 }
 
 pub const MAX_BODY_SIZE: usize = 2usize.pow(14);
+/// PNG uploads can be scaled up and text art is verbose, so imports get a larger limit.
+pub const MAX_IMPORT_BODY_SIZE: usize = 2usize.pow(18);
 
 fn already_exists(e: &Sqlite3Error) -> bool {
     if let Sqlite3Error::Sqlite3(rusqlite::Error::SqliteFailure(e, _)) = e {
@@ -266,15 +541,210 @@ fn already_exists(e: &Sqlite3Error) -> bool {
 async fn put_new_moose(
     State(webdata): State<MooseWebData>,
     session_author: Author,
+    query: Result<Query<TagsQuery>, QueryRejection>,
     payload: Result<Json<Moose>, JsonRejection>,
 ) -> ApiError {
-    let Json(mut moose) = match payload {
+    let Query(TagsQuery { tags }) = match query {
+        Ok(query) => query,
+        Err(e) => {
+            return ApiError::new_with_status(StatusCode::BAD_REQUEST, e.body_text());
+        }
+    };
+    let Json(moose) = match payload {
         Ok(moose) => moose,
         Err(e) => {
             return ApiError::new_with_status(StatusCode::BAD_REQUEST, e);
         }
     };
+    save_new_moose(&webdata, session_author, moose, tags).await
+}
+
+async fn put_new_moose_png(
+    State(webdata): State<MooseWebData>,
+    session_author: Author,
+    query: Result<Query<ImportQuery>, QueryRejection>,
+    png: Bytes,
+) -> ApiError {
+    let Query(ImportQuery {
+        name,
+        tags,
+        description,
+    }) = match query {
+        Ok(query) => query,
+        Err(e) => {
+            return ApiError::new_with_status(StatusCode::BAD_REQUEST, e.body_text());
+        }
+    };
+    match import_png(&png)
+        .and_then(|image| new_moose(name, image))
+        .map(|moose| Moose {
+            description,
+            ..moose
+        }) {
+        Ok(moose) => save_new_moose(&webdata, session_author, moose, tags).await,
+        Err(e) => ApiError::new_with_status(StatusCode::UNPROCESSABLE_ENTITY, e),
+    }
+}
+
+async fn put_new_moose_text(
+    State(webdata): State<MooseWebData>,
+    session_author: Author,
+    query: Result<Query<TextImportQuery>, QueryRejection>,
+    art: String,
+) -> ApiError {
+    let Query(TextImportQuery {
+        name,
+        format,
+        tags,
+        description,
+    }) = match query {
+        Ok(query) => query,
+        Err(e) => {
+            return ApiError::new_with_status(StatusCode::BAD_REQUEST, e.body_text());
+        }
+    };
+    let image = match format {
+        ArtFormat::Irc => import_irc(&art),
+        ArtFormat::Ansi => import_ansi(&art),
+    };
+    match image
+        .and_then(|image| new_moose(name, image))
+        .map(|moose| Moose {
+            description,
+            ..moose
+        }) {
+        Ok(moose) => save_new_moose(&webdata, session_author, moose, tags).await,
+        Err(e) => ApiError::new_with_status(StatusCode::UNPROCESSABLE_ENTITY, e),
+    }
+}
+
+async fn put_new_moose_banner(
+    State(webdata): State<MooseWebData>,
+    session_author: Author,
+    query: Result<Query<ImportQuery>, QueryRejection>,
+    banner: Result<Query<BannerQuery>, QueryRejection>,
+) -> ApiError {
+    let (
+        Query(ImportQuery {
+            name,
+            tags,
+            description,
+        }),
+        Query(banner),
+    ) = match (query, banner) {
+        (Ok(query), Ok(banner)) => (query, banner),
+        (Err(e), _) | (_, Err(e)) => {
+            return ApiError::new_with_status(StatusCode::BAD_REQUEST, e.body_text());
+        }
+    };
+    let image = match moose_banner(&banner) {
+        Ok(image) => image,
+        Err(e) => return ApiError::new_with_status(StatusCode::UNPROCESSABLE_ENTITY, e),
+    };
+    match new_moose(name, image).map(|moose| Moose {
+        description,
+        ..moose
+    }) {
+        Ok(moose) => save_new_moose(&webdata, session_author, moose, tags).await,
+        Err(e) => ApiError::new_with_status(StatusCode::UNPROCESSABLE_ENTITY, e),
+    }
+}
+
+/// Saves a remix of an existing moose at another size, HD unless ?size=default is given.
+async fn put_resampled_moose(
+    State(webdata): State<MooseWebData>,
+    session_author: Author,
+    Path(moose_name): Path<String>,
+    query: Result<Query<ImportQuery>, QueryRejection>,
+    opts: Result<Query<ResampleQuery>, QueryRejection>,
+) -> ApiError {
+    let (
+        Query(ImportQuery {
+            name,
+            tags,
+            description,
+        }),
+        Query(opts),
+    ) = match (query, opts) {
+        (Ok(query), Ok(opts)) => (query, opts),
+        (Err(e), _) | (_, Err(e)) => {
+            return ApiError::new_with_status(StatusCode::BAD_REQUEST, e.body_text());
+        }
+    };
+    let size = opts.size.unwrap_or(MooseSize::HD);
+    let moose = match webdata.db.get_moose(&moose_name).await {
+        Ok(Some(moose)) => moose,
+        Ok(None) => {
+            return ApiError::new_with_status(
+                StatusCode::NOT_FOUND,
+                format!("no such moose: {moose_name}"),
+            );
+        }
+        Err(e) => {
+            log::error!("DB is broken (trying to get moose {moose_name}): {e}");
+            return ApiError::new(e);
+        }
+    };
+    if moose.dimensions == Dimensions::from(size) {
+        return ApiError::new_with_status(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!("{moose_name} is already that size."),
+        );
+    }
+    match new_moose(
+        name,
+        resample_moose(
+            moose,
+            &ResampleQuery {
+                size: Some(size),
+                ..opts
+            },
+        )
+        .image,
+    )
+    .map(|moose| Moose {
+        description,
+        parent: Some(moose_name),
+        ..moose
+    }) {
+        Ok(moose) => save_new_moose(&webdata, session_author, moose, tags).await,
+        Err(e) => ApiError::new_with_status(StatusCode::UNPROCESSABLE_ENTITY, e),
+    }
+}
+
+async fn put_new_moose_draw(
+    State(webdata): State<MooseWebData>,
+    session_author: Author,
+    payload: Result<Json<DrawRequest>, JsonRejection>,
+) -> ApiError {
+    let Json(mut req) = match payload {
+        Ok(req) => req,
+        Err(e) => {
+            return ApiError::new_with_status(StatusCode::BAD_REQUEST, e);
+        }
+    };
+    let tags = std::mem::take(&mut req.tags);
+    let Some(name) = req.name.as_deref() else {
+        return ApiError::new_with_status(
+            StatusCode::BAD_REQUEST,
+            "A name is required to save a drawing.",
+        );
+    };
+    if let Err(e) = validate_name(name) {
+        return ApiError::new_with_status(StatusCode::UNPROCESSABLE_ENTITY, e);
+    }
+    match draw_from_request(&webdata.db, req).await {
+        Ok(moose) => save_new_moose(&webdata, session_author, moose, tags).await,
+        Err(e) => e,
+    }
+}
 
+async fn save_new_moose(
+    webdata: &MooseWebData,
+    session_author: Author,
+    mut moose: Moose,
+    tags: Vec<String>,
+) -> ApiError {
     moose.author = session_author;
 
     // Ignore these user fields by replacing them with defaults.
@@ -289,8 +759,25 @@ async fn put_new_moose(
     }
 
     let db = webdata.db.clone();
+    // a remix can only be of a moose that is still around.
+    if let Some(parent) = &moose.parent {
+        match db.get_moose(parent).await {
+            Ok(Some(_)) => (),
+            Ok(None) => {
+                return ApiError::new_with_status(
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    format!("no such moose to remix: {parent}"),
+                );
+            }
+            Err(e) => {
+                log::error!("DB is broken (trying to get moose {parent}): {e}");
+                return ApiError::new(e);
+            }
+        }
+    }
+
     let moose_name = moose.name.clone();
-    if let Err(e) = db.insert_moose(moose).await {
+    if let Err(e) = db.insert_moose(moose, tags).await {
         if already_exists(&e) {
             return ApiError::new_with_status(
                 StatusCode::UNPROCESSABLE_ENTITY,
@@ -305,6 +792,295 @@ async fn put_new_moose(
     }
 }
 
+/// The moose, if the author is the one who made it.
+async fn owned_moose(
+    db: &Pool,
+    author: &AuthenticatedAuthor,
+    moose_name: &str,
+) -> Result<Moose, ApiError> {
+    let moose = match db.get_moose(moose_name).await {
+        Ok(Some(moose)) => moose,
+        Ok(None) => {
+            return Err(ApiError::new_with_status(
+                StatusCode::NOT_FOUND,
+                format!("no such moose: {moose_name}"),
+            ));
+        }
+        Err(e) => {
+            log::error!("DB is broken (trying to get moose {moose_name}): {e}");
+            return Err(ApiError::new(e));
+        }
+    };
+    let AuthenticatedAuthor::GitHub(login) = author;
+    match &moose.author {
+        Author::GitHub(owner) if owner == login => Ok(moose),
+        _ => Err(ApiError::new_with_status(
+            StatusCode::FORBIDDEN,
+            format!("You can only change your own moose; {moose_name} is not yours."),
+        )),
+    }
+}
+
+/// Swaps the image of a moose for another, keeping the current one as a revision.
+async fn edit_image(
+    db: &Pool,
+    mut moose: Moose,
+    image: Vec<u8>,
+    dimensions: Dimensions,
+) -> ApiError {
+    if let Dimensions::Custom(_, _) = dimensions {
+        return ApiError::new_with_status(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "Custom dimensions are not allowed through the public API.",
+        );
+    }
+    if moose.image == image && moose.dimensions == dimensions {
+        return ApiError::new_with_status(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!("{} is already this image.", moose.name),
+        );
+    }
+    moose.image = image;
+    moose.dimensions = dimensions;
+    let moose_name = moose.name.clone();
+    match db.edit_moose(moose).await {
+        Ok(true) => (),
+        Ok(false) => {
+            return ApiError::new_with_status(
+                StatusCode::NOT_FOUND,
+                format!("no such moose: {moose_name}"),
+            );
+        }
+        Err(e) => {
+            log::error!("Could not edit {moose_name}: {e}");
+            return ApiError::new(e);
+        }
+    }
+    notify_new();
+    log::debug!("Edited moose: {moose_name}");
+    ApiError::new_ok(format!("Saved {moose_name}."))
+}
+
+/// Only the image and dimensions of the submitted moose are used, the rest stays as is.
+async fn put_edit_moose(
+    State(webdata): State<MooseWebData>,
+    author: AuthenticatedAuthor,
+    Path(moose_name): Path<String>,
+    payload: Result<Json<Moose>, JsonRejection>,
+) -> ApiError {
+    let Json(edited) = match payload {
+        Ok(moose) => moose,
+        Err(e) => {
+            return ApiError::new_with_status(StatusCode::BAD_REQUEST, e);
+        }
+    };
+    // only the image and dimensions are taken from the body, so it has to be about this moose.
+    if edited.name != moose_name {
+        return ApiError::new_with_status(
+            StatusCode::BAD_REQUEST,
+            format!("body is for {}, not {moose_name}", edited.name),
+        );
+    }
+    match owned_moose(&webdata.db, &author, &moose_name).await {
+        Ok(moose) => edit_image(&webdata.db, moose, edited.image, edited.dimensions).await,
+        Err(e) => e,
+    }
+}
+
+async fn put_revert_moose(
+    State(webdata): State<MooseWebData>,
+    author: AuthenticatedAuthor,
+    Path((moose_name, revision)): Path<(String, i64)>,
+) -> ApiError {
+    let moose = match owned_moose(&webdata.db, &author, &moose_name).await {
+        Ok(moose) => moose,
+        Err(e) => return e,
+    };
+    match webdata.db.get_moose_revision(&moose_name, revision).await {
+        Ok(Some(old)) => edit_image(&webdata.db, moose, old.image, old.dimensions).await,
+        Ok(None) => ApiError::new_with_status(
+            StatusCode::NOT_FOUND,
+            format!("no such revision: {moose_name} {revision}"),
+        ),
+        Err(e) => {
+            log::error!("DB is broken (trying to get revision {revision} of {moose_name}): {e}");
+            ApiError::new(e)
+        }
+    }
+}
+
+/// Something about an existing moose as JSON that can change at any time, so it is never cached.
+/// `what` names the lookup in the log when the DB breaks.
+async fn moose_json<T: Serialize>(
+    db: &Pool,
+    moose_name: String,
+    what: &str,
+    lookup: impl AsyncFnOnce(&Pool, &str) -> Result<T, Sqlite3Error>,
+) -> ApiResp {
+    match db.get_moose(&moose_name).await {
+        Ok(Some(_)) => (),
+        Ok(None) => return ApiResp::NotFound(moose_name),
+        Err(e) => {
+            log::error!("DB is broken (trying to get moose {moose_name}): {e}");
+            return ApiResp::CustomError(ApiError::new(e));
+        }
+    }
+    match lookup(db, &moose_name).await {
+        Ok(found) => ApiResp::BodyCacheTime(
+            serde_json::to_vec(&found).unwrap(),
+            "application/json",
+            Duration::from_secs(0),
+        ),
+        Err(e) => {
+            log::error!("DB is broken (trying to get {what} of {moose_name}): {e}");
+            ApiResp::CustomError(ApiError::new(e))
+        }
+    }
+}
+
+/// Every revision of a moose, oldest first; any edit adds one.
+async fn get_revisions(State(db): State<MooseWebData>, Path(moose_name): Path<String>) -> ApiResp {
+    moose_json(&db.db, moose_name, "revisions", async |db, moose_name| {
+        db.get_revisions(moose_name).await
+    })
+    .await
+}
+
+async fn get_moose_revision(
+    State(db): State<MooseWebData>,
+    Path((moose_name, revision, format)): Path<(String, i64, String)>,
+    uri: Uri,
+) -> ApiResp {
+    match db.db.get_moose_revision(&moose_name, revision).await {
+        // revisions never change once made.
+        Ok(Some(moose)) => match render_moose(&format, moose, &uri) {
+            Ok((body, ctype)) => ApiResp::Body(body, ctype),
+            Err(e) => ApiResp::CustomError(e),
+        },
+        Ok(None) => ApiResp::CustomError(ApiError::new_with_status(
+            StatusCode::NOT_FOUND,
+            format!("no such revision: {moose_name} {revision}"),
+        )),
+        Err(e) => {
+            log::error!("DB is broken (trying to get revision {revision} of {moose_name}): {e}");
+            ApiResp::CustomError(ApiError::new(e))
+        }
+    }
+}
+
+/// Names of the live remixes of a moose, oldest first.
+async fn get_moose_children(
+    State(db): State<MooseWebData>,
+    Path(moose_name): Path<String>,
+) -> ApiResp {
+    moose_json(&db.db, moose_name, "remixes", async |db, moose_name| {
+        db.get_moose_children(moose_name).await
+    })
+    .await
+}
+
+/// What a moose is a remix of, and what that is a remix of, and so on; see MooseAncestor.
+async fn get_moose_ancestry(
+    State(db): State<MooseWebData>,
+    Path(moose_name): Path<String>,
+) -> ApiResp {
+    moose_json(&db.db, moose_name, "ancestry", async |db, moose_name| {
+        db.get_moose_ancestry(moose_name).await
+    })
+    .await
+}
+
+async fn get_tag_counts(State(db): State<MooseWebData>) -> ApiResp {
+    match db.db.get_tag_counts().await {
+        Ok(tags) => ApiResp::BodyCacheTime(
+            serde_json::to_vec(&tags).unwrap(),
+            "application/json",
+            Duration::from_secs(300),
+        ),
+        Err(e) => {
+            log::error!("DB is broken (trying to get tags): {e}");
+            ApiResp::CustomError(ApiError::new(e))
+        }
+    }
+}
+
+async fn get_moose_tags(State(db): State<MooseWebData>, Path(moose_name): Path<String>) -> ApiResp {
+    moose_json(&db.db, moose_name, "tags", async |db, moose_name| {
+        db.get_tags(moose_name).await
+    })
+    .await
+}
+
+/// Replaces the tags of a moose with a JSON array of tags, e.g. ["meme", "winter"]
+async fn put_moose_tags(
+    State(webdata): State<MooseWebData>,
+    author: AuthenticatedAuthor,
+    Path(moose_name): Path<String>,
+    payload: Result<Json<Vec<String>>, JsonRejection>,
+) -> ApiError {
+    let tags = match payload.map(|Json(tags)| validate_tags(tags)) {
+        Ok(Ok(tags)) => tags,
+        Ok(Err(e)) => return ApiError::new_with_status(StatusCode::UNPROCESSABLE_ENTITY, e),
+        Err(e) => return ApiError::new_with_status(StatusCode::BAD_REQUEST, e),
+    };
+    if let Err(e) = owned_moose(&webdata.db, &author, &moose_name).await {
+        return e;
+    }
+    match webdata.db.set_tags(&moose_name, tags).await {
+        Ok(()) => ApiError::new_ok(format!("Tagged {moose_name}.")),
+        Err(e) => {
+            log::error!("Could not tag {moose_name}: {e}");
+            ApiError::new(e)
+        }
+    }
+}
+
+async fn get_tag_page(
+    State(db): State<MooseWebData>,
+    author: Option<AuthenticatedAuthor>,
+    Path((tag, page_num)): Path<(String, usize)>,
+) -> ApiResp {
+    let meese = db
+        .db
+        .get_tag_page(&tag, page_num, author)
+        .await
+        .unwrap_or_else(|err| {
+            log::warn!("{err}");
+            MooseSearchPage::default()
+        });
+    let meese = serde_json::to_vec(&meese).unwrap();
+    ApiResp::BodyCacheTime(meese, "application/json", Duration::from_secs(300))
+}
+
+/// Owners can delete their own moose, admins can delete any moose.
+async fn delete_moose(
+    State(webdata): State<MooseWebData>,
+    author: AuthenticatedAuthor,
+    Path(moose_name): Path<String>,
+) -> ApiError {
+    let AuthenticatedAuthor::GitHub(login) = &author;
+    if !webdata.admins.contains(login)
+        && let Err(e) = owned_moose(&webdata.db, &author, &moose_name).await
+    {
+        return e;
+    }
+    match webdata.db.delete_moose(&moose_name).await {
+        Ok(true) => {
+            notify_new();
+            log::info!("{login} deleted moose: {moose_name}");
+            ApiError::new_ok(format!("Deleted {moose_name}."))
+        }
+        Ok(false) => ApiError::new_with_status(
+            StatusCode::NOT_FOUND,
+            format!("no such moose: {moose_name}"),
+        ),
+        Err(e) => {
+            log::error!("Could not delete {moose_name}: {e}");
+            ApiError::new(e)
+        }
+    }
+}
+
 async fn upvote_moose(
     state: State<MooseWebData>,
     author: AuthenticatedAuthor,
@@ -329,11 +1105,13 @@ async fn vote_moose(
 ) -> ApiError {
     let db = webdata.db.clone();
     let status = match unvote {
-        VoteFlag::None => db.unvote_moose(author, moose.clone()).await,
+        VoteFlag::None => db.unvote_moose(author, moose.clone()).await.map(|_| true),
         VoteFlag::Up => db.upvote_moose(author, moose.clone()).await,
         VoteFlag::Down => todo!(),
     };
-    if let Err(e) = status {
+    if let Ok(false) = status {
+        ApiError::new_with_status(StatusCode::NOT_FOUND, format!("no such moose: {moose}"))
+    } else if let Err(e) = status {
         if already_exists(&e) {
             ApiError::new_with_status(
                 StatusCode::UNPROCESSABLE_ENTITY,
@@ -386,24 +1164,74 @@ async fn get_dump() -> Response {
 }
 
 pub fn routes(ratelim: Option<Ratelim>) -> Router<MooseWebData> {
-    let new_method = put(put_new_moose).post(put_new_moose);
+    let ratelim = ratelim.map(BucketRatelim::from);
+    // everything that saves a moose shares the same rate limit.
+    let limited = |method: MethodRouter<MooseWebData>| match ratelim.clone() {
+        Some(rl) => method.route_layer(rl),
+        None => method,
+    };
+    let new_png_method = put(put_new_moose_png)
+        .post(put_new_moose_png)
+        .layer(DefaultBodyLimit::max(MAX_IMPORT_BODY_SIZE));
+    let new_text_method = put(put_new_moose_text)
+        .post(put_new_moose_text)
+        .layer(DefaultBodyLimit::max(MAX_IMPORT_BODY_SIZE));
     Router::new()
         .route("/api-helper/resolve/{moose_name}", get(resolve_moose))
-        .route("/moose/{moose_name}", get(get_moose))
+        .route("/moose/{moose_name}", get(get_moose).delete(delete_moose))
+        .route("/moose/{moose_name}/children", get(get_moose_children))
+        .route("/moose/{moose_name}/ancestry", get(get_moose_ancestry))
+        .route(
+            "/moose/{moose_name}/tags",
+            get(get_moose_tags).put(put_moose_tags),
+        )
         .route("/img/{moose_name}", get(get_moose))
+        .route("/gif/{moose_name}", get(get_moose))
+        .route("/svg/{moose_name}", get(get_moose))
+        .route("/card/{moose_name}", get(get_moose))
         .route("/irc/{moose_name}", get(get_moose))
         .route("/term/{moose_name}", get(get_moose))
+        .route("/sixel/{moose_name}", get(get_moose))
+        .route("/emoji/{moose_name}", get(get_moose))
+        .route("/ascii/{moose_name}", get(get_moose))
+        .route("/compose/{format}", get(get_composed_moose))
+        .route("/banner/{format}", get(get_banner))
+        .route("/draw/{format}", post(post_draw))
+        .route("/revisions/{moose_name}", get(get_revisions))
+        .route(
+            "/revisions/{moose_name}/{revision}/{format}",
+            get(get_moose_revision),
+        )
         .route("/page", get(get_page_count))
         .route("/page/{page_num}", get(get_page))
+        .route("/sprite/{page_num}", get(get_page_sprite))
+        .route("/sprite/{page_num}/map", get(get_page_sprite_map))
         .route("/nav/{page_num}", get(get_page_nav_range))
         .route("/search", get(get_search_page))
+        .route("/tags", get(get_tag_counts))
+        .route("/tag/{tag}/page/{page_num}", get(get_tag_page))
+        .route("/new", limited(put(put_new_moose).post(put_new_moose)))
+        .route("/new/png", limited(new_png_method))
+        .route("/new/text", limited(new_text_method))
         .route(
-            "/new",
-            if let Some(rl) = ratelim {
-                new_method.route_layer::<BucketRatelim>(rl.into())
-            } else {
-                new_method
-            },
+            "/new/banner",
+            limited(put(put_new_moose_banner).post(put_new_moose_banner)),
+        )
+        .route(
+            "/new/resample/{moose_name}",
+            limited(put(put_resampled_moose).post(put_resampled_moose)),
+        )
+        .route(
+            "/new/draw",
+            limited(put(put_new_moose_draw).post(put_new_moose_draw)),
+        )
+        .route(
+            "/edit/{moose_name}",
+            limited(put(put_edit_moose).post(put_edit_moose)),
+        )
+        .route(
+            "/revert/{moose_name}/{revision}",
+            limited(put(put_revert_moose).post(put_revert_moose)),
         )
         .route(
             "/upvote/{moose_name}",